@@ -2,18 +2,30 @@
 - This module should handle the rendering and layout of the thing
 */
 
-use crate::app::{App, Units};
+use crate::app::{
+    self, format_interval, App, FreqChart, HistoryChart, Panel, ProcAction, ProcSort, SensorRow,
+    View,
+};
+use crate::config::Config;
+use crate::events::{KeyActions, Keymap};
+use crate::history;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     prelude::Alignment,
     style::{Color, Modifier, Style},
     symbols,
-    text::{Span, Text},
+    text::{Line, Span, Text},
     widgets::{
-        Axis, BarChart, Block, Borders, Chart, Dataset, Gauge, GraphType, Padding, Paragraph, Wrap,
+        block::{Position, Title},
+        Axis, Bar, BarChart, BarGroup, Block, Borders, Chart, Clear, Dataset, Gauge, GraphType,
+        Padding, Paragraph, Row, Sparkline, Table, TableState, Wrap,
     },
     Frame,
 };
+use sys_mon::battery;
+use sys_mon::loads::CoreLoad;
+use sys_mon::memory::MemInfo;
+use sys_mon::psi::{Pressure, PsiLine};
 
 // Main function used to render the UI.
 // passed as a closure to the draw function which passes the frame size to it.
@@ -54,36 +66,378 @@ pub fn ui(f: &mut Frame, app: &App) {
 
     ////////////// Title Box///////////////
     // Border box thing
-    let title_block = Block::default()
+    let (hostname, kernel) = app.get_host();
+    let mut title_block = Block::default()
         .borders(Borders::ALL)
         .style(Style::default())
-        .title(" OOga Booga ");
+        .title(format!(" {} ", hostname))
+        .title(Title::from(format!(" kernel {} ", kernel)).alignment(Alignment::Right));
+    // collectors that are failing, the reasons are on the views that use them
+    let errors = app.get_collector_errors();
+    if !errors.is_empty() {
+        let names: Vec<&str> = errors.keys().map(|k| k.as_str()).collect();
+        title_block = title_block.title(
+            Title::from(Span::styled(
+                format!(" no data: {} ", names.join(" ")),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Right),
+        );
+    }
     // Paragraph widget takes ownership of title_block
-    let title = Paragraph::new(Text::styled(
-        "system stats",
-        Style::default().add_modifier(Modifier::BOLD),
-    ))
+    let title = Paragraph::new(Line::from(vec![
+        Span::styled("load ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            app.get_load_average(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("  up ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            app.get_uptime(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("  booted ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            app.get_boot_time(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("  every ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            format_interval(app.get_interval()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("  charts ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            app.window.label(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+    ]))
     .block(title_block)
     .alignment(Alignment::Center);
 
     //-----------------------------------------------------//
-    // SPlit in 2 blocks for info
+    match app.view {
+        View::Overview => draw_overview(f, app, chunks[1]),
+        View::Mounts => draw_mounts(f, app, chunks[1]),
+        View::Disks => draw_disks(f, app, chunks[1]),
+        View::Network => draw_network(f, app, chunks[1]),
+        View::Processes => draw_processes(f, app, chunks[1]),
+        View::Memory => draw_memory(f, app, chunks[1]),
+        View::Sensors => draw_sensors(f, app, chunks[1]),
+        View::Battery => draw_battery(f, app, chunks[1]),
+    }
+
+    //Quit message box, the title doubles as the status line
+    let mut footer_block = Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default().bg(app.config().theme.footer));
+    if let Some((msg, err)) = app.get_status() {
+        let color = if err { Color::Red } else { Color::DarkGray };
+        footer_block = footer_block.title(Span::styled(
+            format!(" {} ", msg),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ));
+    }
+    // Quit paragraph
+    let footer = Paragraph::new(Text::styled(
+        footer_text(app),
+        Style::default()
+            .fg(Color::DarkGray)
+            .bg(app.config().theme.footer)
+            .add_modifier(Modifier::BOLD),
+    ))
+    .block(footer_block);
+
+    // RENDER STUFF
+    f.render_widget(title, chunks[0]);
+    f.render_widget(footer, chunks[2]);
+
+    if let Some(action) = &app.popup {
+        draw_popup(f, &app.config().keys, action);
+    }
+}
+
+// Confirmation box for sending a signal or renicing
+fn draw_popup(f: &mut Frame, keymap: &Keymap, action: &ProcAction) {
+    let confirm = popup_keys(keymap, &[KeyActions::Confirm]);
+    // renice's own key backs out of the popup too
+    let cancel = popup_keys(keymap, &[KeyActions::Renice, KeyActions::Cancel]);
+    let (title, question, hint) = match action {
+        ProcAction::Signal {
+            pid, name, label, ..
+        } => (
+            format!(" {} ", label),
+            format!("Send {} to {} ({})?", label, pid, name),
+            format!("{} to send, {} to cancel", confirm, cancel),
+        ),
+        ProcAction::Renice { pid, name, nice } => (
+            " Renice ".to_owned(),
+            format!("Set nice of {} ({}) to {}", pid, name, nice),
+            format!(
+                "{} to change, {} to apply, {} to cancel",
+                popup_keys(keymap, &[KeyActions::Up, KeyActions::Down]),
+                confirm,
+                cancel
+            ),
+        ),
+    };
+
+    let rect = centered_rect(f.size(), 50, 30);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::LightYellow))
+        .title(title);
+    let text = Paragraph::new(vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            question,
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
+    .block(block);
+
+    f.render_widget(Clear, rect);
+    f.render_widget(text, rect);
+}
+
+// help text for the footer, the first half is the same on every view. Keys
+// come from the keymap so rebinding one in the config shows up here too
+fn footer_text(app: &App) -> String {
+    use KeyActions::*;
+    if app.view == View::Processes && app.is_typing() {
+        return "Type to filter, 'ENTER' to keep it, 'ESC' to clear it".to_owned();
+    }
+    let (hints, note): (&[(&[KeyActions], &str)], &str) = match app.view {
+        View::Overview => (
+            &[
+                (&[ToggleUnits], "units"),
+                (&[ClearTemp], "clear temps"),
+                (&[CycleFreqChart], "freq chart"),
+                (&[FocusChart], "pick chart"),
+                (&[CycleGraph], "chart style"),
+                (&[SlowerSampling, FasterSampling], "interval"),
+                (&[NarrowerWindow, WiderWindow], "window"),
+            ],
+            "",
+        ),
+        View::Mounts => (
+            &[
+                (&[Up, Down], "to scroll"),
+                (&[TogglePseudo], "to toggle pseudo filesystems"),
+            ],
+            "",
+        ),
+        View::Disks => (
+            &[(&[Up, Down], "to scroll"), (&[CycleGraph], "chart style")],
+            "",
+        ),
+        View::Network => (
+            &[
+                (&[Up, Down], "to pick the charted interface"),
+                (&[CycleGraph], "chart style"),
+            ],
+            "",
+        ),
+        View::Memory => (
+            &[(&[CycleGraph], "chart style")],
+            "bars are green used, blue buffers, yellow cache",
+        ),
+        View::Sensors => (
+            &[
+                (&[Up, Down], "to scroll"),
+                (&[ToggleUnits], "to change units"),
+            ],
+            "yellow is near a limit",
+        ),
+        View::Battery => (
+            &[(&[CycleGraph], "chart style")],
+            "health is full capacity against design, rate is the live draw",
+        ),
+        View::Processes => (
+            &[
+                (&[CycleSort], "sort"),
+                (&[ReverseSort], "reverse"),
+                (&[ToggleTree], "tree"),
+                (&[StartFilter], "filter"),
+                (&[Terminate], "term"),
+                (&[Kill], "kill"),
+                (&[StopContinue], "stop"),
+                (&[Renice], "nice"),
+            ],
+            "",
+        ),
+    };
+
+    let keymap = &app.config().keys;
+    let mut parts = Vec::new();
+    if let Some(quit) = keymap.names(Quit).first() {
+        parts.push(format!("'{}' quit", quit));
+    }
+    if let Some(views) = view_keys(keymap) {
+        parts.push(format!("'{}' views", views));
+    }
+    if !note.is_empty() {
+        parts.push(note.to_owned());
+    }
+    // an action left without a key has nothing to show
+    for (actions, label) in hints {
+        let keys: Option<Vec<String>> = actions
+            .iter()
+            .map(|a| keymap.names(*a).into_iter().next())
+            .collect();
+        if let Some(keys) = keys {
+            parts.push(format!("'{}' {}", keys.join("/"), label));
+        }
+    }
+    parts.join(", ")
+}
+
+// the keys for the views, as '1-8' while they are still a run of digits
+fn view_keys(keymap: &Keymap) -> Option<String> {
+    let views = [
+        View::Overview,
+        View::Mounts,
+        View::Disks,
+        View::Network,
+        View::Processes,
+        View::Memory,
+        View::Sensors,
+        View::Battery,
+    ];
+    let keys: Vec<String> = views
+        .iter()
+        .filter_map(|v| keymap.names(KeyActions::SwitchView(*v)).into_iter().next())
+        .collect();
+    let digits: Vec<u32> = keys
+        .iter()
+        .filter_map(|k| k.parse().ok().filter(|_| k.len() == 1))
+        .collect();
+    let run = digits.len() == keys.len() && digits.windows(2).all(|w| w[1] == w[0] + 1);
+    match keys.len() {
+        0 => None,
+        n if run && n > 2 => Some(format!("{}-{}", keys[0], keys[n - 1])),
+        _ => Some(keys.join("/")),
+    }
+}
+
+// the popup's key hint, every key that does it since there is room
+fn popup_keys(keymap: &Keymap, actions: &[KeyActions]) -> String {
+    actions
+        .iter()
+        .flat_map(|a| keymap.names(*a))
+        .map(|k| format!("'{}'", k))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Stack the panels of one overview column that are switched on. When none of
+// them stretches the last one takes the leftover space
+fn overview_column(app: &App, area: Rect, panels: Vec<(Panel, Constraint)>) -> Vec<(Panel, Rect)> {
+    let mut shown: Vec<_> = panels.into_iter().filter(|(p, _)| app.shows(*p)).collect();
+    if !shown.iter().any(|(_, c)| matches!(c, Constraint::Min(_))) {
+        if let Some((_, last)) = shown.last_mut() {
+            *last = Constraint::Min(0);
+        }
+    }
+    let constraints: Vec<Constraint> = shown.iter().map(|(_, c)| *c).collect();
+    let rects = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(area);
+    shown
+        .into_iter()
+        .map(|(p, _)| p)
+        .zip(rects.iter().copied())
+        .collect()
+}
+
+// CPU load, temperature, battery and memory
+fn draw_overview(f: &mut Frame, app: &App, area: Rect) {
+    // SPlit in 2 blocks for info, a column with nothing switched on gives its half away
+    let left = [Panel::Load, Panel::Cores, Panel::Psi, Panel::Memory];
+    let right = [
+        Panel::Temp,
+        Panel::MemoryHistory,
+        Panel::Battery,
+        Panel::Power,
+    ];
+    let halves = match (
+        left.iter().any(|p| app.shows(*p)),
+        right.iter().any(|p| app.shows(*p)),
+    ) {
+        (true, false) => [100, 0],
+        (false, true) => [0, 100],
+        _ => [50, 50],
+    };
     let info_chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(chunks[1]);
+        .constraints(halves.map(Constraint::Percentage))
+        .split(area);
+
+    // figure out how many rows the per core grid needs at this width
+    let cores = app.get_core_loads();
+    let core_lines = core_grid(cores, info_chunks[0].width.saturating_sub(2));
+    let core_height = if cores.is_empty() {
+        0
+    } else {
+        core_lines.len() as u16 + 2
+    };
 
     // SPLIT chunk 0 for memory an load
-    let loads_mem = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(6), Constraint::Max(3)])
-        .split(info_chunks[0]);
+    let mut placed = overview_column(
+        app,
+        info_chunks[0],
+        vec![
+            (Panel::Load, Constraint::Min(6)),
+            (Panel::Cores, Constraint::Length(core_height)),
+            // hidden on kernels without PSI
+            (
+                Panel::Psi,
+                Constraint::Length(if app.get_psi().is_some() { 6 } else { 0 }),
+            ),
+            (Panel::Memory, Constraint::Length(5)),
+        ],
+    );
 
-    //SPlit again for temp and battery life
-    let battery_temp_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Max(7)])
-        .split(info_chunks[1]);
+    //SPlit again for temp and battery life, battery and RAPL power share the bottom row
+    let bottom: Vec<Panel> = [Panel::Battery, Panel::Power]
+        .into_iter()
+        .filter(|p| app.shows(*p))
+        .collect();
+    let mut right = vec![
+        (Panel::Temp, Constraint::Percentage(45)),
+        (Panel::MemoryHistory, Constraint::Min(6)),
+    ];
+    if let Some(first) = bottom.first() {
+        right.push((*first, Constraint::Length(7)));
+    }
+    for (panel, rect) in overview_column(app, info_chunks[1], right) {
+        if bottom.first() != Some(&panel) {
+            placed.push((panel, rect));
+            continue;
+        }
+        let row = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Ratio(1, bottom.len() as u32);
+                bottom.len()
+            ])
+            .split(rect);
+        placed.extend(bottom.iter().copied().zip(row.iter().copied()));
+    }
+    // where a panel ended up, empty if it is switched off
+    let area_of = |panel: Panel| {
+        placed
+            .iter()
+            .find(|(p, _)| *p == panel)
+            .map(|(_, rect)| *rect)
+            .unwrap_or_default()
+    };
 
     //////  +++++++++++ Battery Block ++++++++++++++ ////////
     let battery_block = Block::default()
@@ -91,17 +445,22 @@ pub fn ui(f: &mut Frame, app: &App) {
         .style(Style::default())
         .title(" Battery 🔋 ");
     // Split again
-    let battery_space = battery_block.inner(battery_temp_chunks[1]);
+    let battery_space = battery_block.inner(area_of(Panel::Battery));
     let battery_recs = Layout::default()
         .constraints([Constraint::Max(3), Constraint::Min(4)])
         .split(battery_space);
 
     // Battery widget Paragraph
-    let ac_power = Paragraph::new(Text::styled(format!("AC is plugged in"), Style::default()))
-        .alignment(Alignment::Center);
+    let ac_power = Paragraph::new(Text::styled(
+        format!("AC is plugged in, {}", app.get_battery_time()),
+        Style::default(),
+    ))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
 
     let battery_percent = Paragraph::new(Text::styled(app.get_battery_time(), Style::default()))
-        .alignment(Alignment::Center);
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
     //
     // Battery Gauge Widget
     let battery_gauge = Gauge::default()
@@ -116,70 +475,136 @@ pub fn ui(f: &mut Frame, app: &App) {
     let temp_block = Block::default()
         .borders(Borders::ALL)
         .style(Style::default())
-        .title(" CPU Temperature 🔥 ");
+        .border_style(chart_border(app, HistoryChart::Temp))
+        .title(match app.freq_chart {
+            FreqChart::Off => " CPU Temperature 🔥 ",
+            FreqChart::Overlay => " CPU Temperature 🔥 + Frequency ",
+            FreqChart::Only => " CPU Frequency ⏱️  ",
+        });
 
     // Match units to decide what to display the digital thing in
-    let unit = match app.units {
-        Units::Celcius => "C",
-        Units::Fahrenheit => "F",
-    };
+    let unit = app.units.symbol();
 
-    let temp = Paragraph::new(Text::styled(
-        app.get_temp().to_string() + unit,
-        Style::default(),
-    ))
-    .alignment(Alignment::Center);
+    // frequency, governor and throttling go next to the temperature when we have them
+    let freq = app.get_freq_summary();
+    let mut readout = vec![Span::raw(app.get_temp().to_string() + unit)];
+    if let Some(summary) = &freq {
+        readout.push(Span::styled(
+            format!(
+                "   {:.2} GHz ({:.1}-{:.1})  {}",
+                summary.avg_mhz / 1000.0,
+                summary.min_mhz / 1000.0,
+                summary.max_mhz / 1000.0,
+                summary.governor
+            ),
+            Style::default().fg(Color::Gray),
+        ));
+        if let Some(throttles) = summary.throttles {
+            let color = if throttles > 0 {
+                Color::LightRed
+            } else {
+                Color::Gray
+            };
+            readout.push(Span::styled(
+                format!("  throttled {}x", throttles),
+                Style::default().fg(color),
+            ));
+        }
+    }
+    let temp = Paragraph::new(Line::from(readout)).alignment(Alignment::Center);
 
     // CHART FOR TEMP
-    // DATATSET
-    let dataset = vec![Dataset::default()
-        .marker(symbols::Marker::Dot)
-        .graph_type(GraphType::Line)
-        .style(Style::default().fg(Color::LightBlue))
-        .data(app.get_temp_points())];
-
-    let chart = Chart::new(dataset.clone())
-        .x_axis(
-            Axis::default()
-                .title("")
-                .style(Style::default())
-                .bounds([0.0, app.get_temp_points().len() as f64]),
-        )
-        .y_axis(
-            Axis::default()
-                .title(format!("Temp ({unit})"))
-                .style(Style::default())
-                .bounds([0.0, 120.0])
-                .labels(
-                    ["0", "20", "40", "60", "80", "100", "120"]
-                        .iter()
-                        .cloned()
-                        .map(Span::from)
-                        .collect(),
-                ),
-        );
+    // DATATSET, one per sensor when we found any, otherwise the single cpu temp
+    let sensors = app.get_sensor_points();
+    let temp_points = app.get_temp_points();
+    let temp_style = app.graph(HistoryChart::Temp);
+    let mut plots: Vec<Plot> = if sensors.is_empty() {
+        vec![Plot {
+            name: String::new(),
+            color: Color::LightBlue,
+            points: &temp_points,
+        }]
+    } else {
+        sensors
+            .iter()
+            .zip(SENSOR_COLORS.iter().cycle())
+            .map(|((sensor, points), color)| Plot {
+                name: match sensor.alarm() {
+                    Some(alarm) => format!("{} {}", sensor.name(), alarm),
+                    None => sensor.name(),
+                },
+                color: *color,
+                points,
+            })
+            .collect()
+    };
 
-    let chart_f = Chart::new(dataset)
-        .x_axis(
-            Axis::default()
-                .title("")
-                .style(Style::default())
-                .bounds([0.0, app.get_temp_points().len() as f64]),
-        )
-        .y_axis(
-            Axis::default()
-                .title(format!("Temp ({unit})"))
-                .style(Style::default())
-                .bounds([0.0, 120.0])
-                .labels(
-                    ["32", "68", "104", "140", "176", "212", "248"]
-                        .iter()
-                        .cloned()
-                        .map(Span::from)
-                        .collect(),
-                ),
-        );
-    let temp_inner = temp_block.inner(battery_temp_chunks[0]);
+    // frequency as a percent of max so it can share the temperature axis
+    let freq_percent: Vec<(f64, f64)> = match &freq {
+        Some(summary) if summary.max_mhz > 0.0 => app
+            .get_freq_points()
+            .iter()
+            .map(|(x, mhz)| (*x, mhz / summary.max_mhz * 100.0))
+            .collect(),
+        _ => Vec::new(),
+    };
+    if let FreqChart::Overlay = app.freq_chart {
+        plots.push(Plot {
+            name: "freq % of max".to_owned(),
+            color: Color::White,
+            points: &freq_percent,
+        });
+    }
+
+    // frequency on its own in GHz
+    let freq_points = app.get_freq_points();
+    let freq_top = freq.as_ref().map(|f| f.max_mhz).unwrap_or(0.0).max(1000.0) * 1.1;
+    let freq_plots = [Plot {
+        name: "avg".to_owned(),
+        color: Color::LightCyan,
+        points: &freq_points,
+    }];
+    let freq_axis = Axis::default().title("GHz").labels(vec![
+        Span::from("0"),
+        Span::from(format!("{:.1}", freq_top / 2000.0)),
+        Span::from(format!("{:.1}", freq_top / 1000.0)),
+    ]);
+
+    // hwmon limits as flat lines across the window, only for the styles that join points up
+    let joined = matches!(temp_style, app::GraphType::Line | app::GraphType::Braille);
+    let [first, last] = app.get_chart_window();
+    // only the sensors on the chart, the cpu temp fallback has no limits
+    let plotted: Vec<_> = sensors.iter().map(|(sensor, _)| *sensor).collect();
+    let (max, crit) = app.get_temp_limits(&plotted);
+    let max_line: Vec<(f64, f64)> = max.iter().flat_map(|m| [(first, *m), (last, *m)]).collect();
+    let crit_line: Vec<(f64, f64)> = crit
+        .iter()
+        .flat_map(|c| [(first, *c), (last, *c)])
+        .collect();
+    for (name, line, color) in [
+        ("max", &max_line, Color::LightYellow),
+        ("crit", &crit_line, Color::LightRed),
+    ] {
+        if let (Some((_, value)), true) = (line.first(), joined) {
+            plots.push(Plot {
+                name: format!("{} {:.0}{}", name, value, unit),
+                color,
+                points: line,
+            });
+        }
+    }
+
+    // fit the y axis to what is on screen
+    let shown: Vec<f64> = plots
+        .iter()
+        .flat_map(|plot| plot.points.iter().map(|p| p.1))
+        .collect();
+    let y_bounds = fit_bounds(&shown, 10.0);
+    let temp_axis = Axis::default()
+        .title(format!("Temp ({unit})"))
+        .style(Style::default())
+        .labels(bound_labels(y_bounds));
+    let temp_inner = temp_block.inner(area_of(Panel::Temp));
     let temp_chunks = Layout::default()
         .constraints([Constraint::Max(3), Constraint::Min(10)])
         .direction(Direction::Vertical)
@@ -215,15 +640,22 @@ pub fn ui(f: &mut Frame, app: &App) {
     // Split battery chunks 1 to center chart
 
     // I need the width to find center
-    let w = loads_mem[0].width;
+    let w = area_of(Panel::Load).width;
     let load_bars_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Max((&w - 35) / 2),
+            Constraint::Max(w.saturating_sub(35) / 2),
             Constraint::Min(35),
-            Constraint::Max((&w - 35) / 2),
+            Constraint::Max(w.saturating_sub(35) / 2),
         ])
-        .split(loads_mem[0]);
+        .split(area_of(Panel::Load));
+    // ++++++++ PER CORE GRID ++++++++ //
+    let cores_block = Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default())
+        .title(format!(" Per Core ({}) ", cores.len()));
+    let cores_grid = Paragraph::new(core_lines).block(cores_block);
+
     // ++++++++ MEMORY USAGE BLOCK -++++++++//
     let mem_block = Block::default()
         .borders(Borders::ALL)
@@ -231,46 +663,1139 @@ pub fn ui(f: &mut Frame, app: &App) {
         .title(" Memory Usage 🧠 ");
 
     let (x, y) = app.get_mem();
-    let memory = Paragraph::new(Text::styled(
-        format!("{} Used / {} Total", x, y),
-        Style::default(),
-    ))
-    .block(mem_block);
+    let mut mem_lines = vec![Line::from(format!("{} Used / {} Total", x, y))];
+    if let Some(mem) = app.get_meminfo() {
+        let width = mem_block
+            .inner(area_of(Panel::Memory))
+            .width
+            .saturating_sub(5) as usize;
+        let mut bar = memory_bar(mem, width);
+        bar.push(Span::raw(format!("{:>4.0}%", mem.percent(mem.used()))));
+        mem_lines.push(Line::from(bar));
+        let mut swap = usage_bar(app.config(), mem.swap_percent(), width);
+        swap.push(Span::raw(format!("{:>4.0}%", mem.swap_percent())));
+        mem_lines.push(Line::from(swap));
+    }
+    let memory = Paragraph::new(mem_lines).block(mem_block);
+
+    // RENDER STUFF, only what is switched on
+    if app.shows(Panel::Load) {
+        f.render_widget(load_block, area_of(Panel::Load));
+        f.render_widget(load_bars, load_bars_chunks[1]);
+    }
+    if app.shows(Panel::Cores) && !cores.is_empty() {
+        f.render_widget(cores_grid, area_of(Panel::Cores));
+    }
+    if let (true, Some(psi)) = (app.shows(Panel::Psi), app.get_psi()) {
+        let rows = [("cpu", &psi.cpu), ("memory", &psi.memory), ("io", &psi.io)];
+        f.render_widget(psi_table(app.config(), &rows), area_of(Panel::Psi));
+    }
+    if app.shows(Panel::Memory) {
+        f.render_widget(memory, area_of(Panel::Memory));
+    }
+    if app.shows(Panel::MemoryHistory) {
+        // memory history next to the temperature
+        draw_memory_history(f, app, area_of(Panel::MemoryHistory));
+    }
+    if app.shows(Panel::Temp) {
+        f.render_widget(temp_block, area_of(Panel::Temp));
+        let (plots, axis, bounds) = match app.freq_chart {
+            FreqChart::Only => (&freq_plots[..], freq_axis, [0.0, freq_top]),
+            _ => (&plots[..], temp_axis, y_bounds),
+        };
+        // the temp block already has the border
+        draw_history(
+            f,
+            app,
+            temp_chunks[1],
+            Block::default(),
+            HistoryChart::Temp,
+            plots,
+            axis,
+            bounds,
+        );
+        f.render_widget(temp, temp_chunks[0]);
+    }
+    if app.shows(Panel::Battery) {
+        f.render_widget(battery_block, area_of(Panel::Battery));
+        if app.is_on_ac_power() {
+            f.render_widget(ac_power, battery_recs[0]);
+        } else {
+            f.render_widget(battery_percent, battery_recs[0]);
+        }
+        f.render_widget(battery_gauge, battery_recs[1]);
+    }
+    if app.shows(Panel::Power) {
+        draw_power(f, app, area_of(Panel::Power));
+    }
+}
 
-    //Quit message box
-    let footer_block = Block::default()
+// RAPL draw per zone with a sparkline of the package total
+fn draw_power(f: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
         .borders(Borders::ALL)
-        .style(Style::default().bg(Color::LightYellow));
-    // Quit paragraph
-    let footer = Paragraph::new(Text::styled(
-        "Press 'Q' to quit, 'TAB' to change units, 'C' to clear temp history",
+        .border_style(chart_border(app, HistoryChart::Power));
+    let zones = match app.get_power_zones() {
+        Ok(zones) if !zones.is_empty() => zones,
+        Ok(_) => {
+            let wait = Paragraph::new("waiting for a second sample")
+                .alignment(Alignment::Center)
+                .block(block.title(" Power ⚡ "));
+            f.render_widget(wait, area);
+            return;
+        }
+        Err(why) => {
+            let na = Paragraph::new(Text::styled(why, Style::default().fg(Color::DarkGray)))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .block(block.title(" Power ⚡ NA "));
+            f.render_widget(na, area);
+            return;
+        }
+    };
+    let total: f64 = zones.iter().filter(|z| z.package).map(|z| z.watts).sum();
+    let block = block.title(format!(" Power ⚡ {:.1} W ", total));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // zones on top, the sparkline gets whatever is left
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(zones.len().min(inner.height as usize / 2) as u16),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+    let lines: Vec<Line> = zones
+        .iter()
+        .map(|z| {
+            Line::from(vec![
+                Span::raw(format!("{:<18}", z.name)),
+                Span::styled(
+                    format!("{:>7.2} W", z.watts),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), chunks[0]);
+    let power = app.get_power_history();
+    let top = power.iter().map(|p| p.1).fold(1.0, f64::max).ceil();
+    let plots = [Plot {
+        name: String::new(),
+        color: Color::LightCyan,
+        points: &power,
+    }];
+    let axis = Axis::default().labels(vec![
+        Span::from("0"),
+        Span::from(format!("{:.0}", top / 2.0)),
+        Span::from(format!("{:.0}", top)),
+    ]);
+    draw_history(
+        f,
+        app,
+        chunks[1],
+        Block::default(),
+        HistoryChart::Power,
+        &plots,
+        axis,
+        [0.0, top],
+    );
+}
+
+// some/full stall averages per resource
+fn psi_table<'a>(config: &Config, rows: &[(&'a str, &Pressure)]) -> Table<'a> {
+    let cell = |v: f32| {
+        Line::from(Span::styled(
+            format!("{:>6.2}", v),
+            Style::default().fg(config.psi_color(v as f64)),
+        ))
+    };
+    let cells = |line: Option<&PsiLine>| match line {
+        Some(l) => vec![cell(l.avg10), cell(l.avg60), cell(l.avg300)],
+        None => vec![Line::from("    NA"), Line::from(""), Line::from("")],
+    };
+    let header = Row::new(vec![
+        "", "some 10s", "60s", "300s", "full 10s", "60s", "300s",
+    ])
+    .style(Style::default().fg(Color::DarkGray));
+    let body: Vec<Row> = rows
+        .iter()
+        .map(|(name, pressure)| {
+            let mut row = vec![Line::from(*name)];
+            row.extend(cells(Some(&pressure.some)));
+            row.extend(cells(pressure.full.as_ref()));
+            Row::new(row)
+        })
+        .collect();
+    Table::new(body).header(header).widths(&PSI_WIDTHS).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Pressure Stall (%) ⏳ "),
+    )
+}
+
+const PSI_WIDTHS: [Constraint; 7] = [
+    Constraint::Length(6),
+    Constraint::Length(8),
+    Constraint::Length(6),
+    Constraint::Length(6),
+    Constraint::Length(8),
+    Constraint::Length(6),
+    Constraint::Length(6),
+];
+
+// used, buffers, cache and whats actually free, stacked
+fn memory_bar(mem: &MemInfo, width: usize) -> Vec<Span<'static>> {
+    stacked_bar(
+        &[
+            (mem.percent(mem.used()), Color::LightGreen),
+            (mem.percent(mem.buffers), Color::LightBlue),
+            (mem.percent(mem.cached), Color::LightYellow),
+        ],
+        width,
+        "█",
+        "░",
+    )
+}
+
+// used memory and swap over time
+fn draw_memory_history(f: &mut Frame, app: &App, area: Rect) {
+    let (mem, swap) = (app.get_mem_points(), app.get_swap_points());
+    let plots = [
+        Plot {
+            name: "mem".to_owned(),
+            color: Color::LightGreen,
+            points: &mem,
+        },
+        Plot {
+            name: "swap".to_owned(),
+            color: Color::LightMagenta,
+            points: &swap,
+        },
+    ];
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(chart_border(app, HistoryChart::Memory))
+        .title(" Memory History (%) ");
+    draw_history(
+        f,
+        app,
+        area,
+        block,
+        HistoryChart::Memory,
+        &plots,
+        percent_axis(),
+        [0.0, 100.0],
+    );
+}
+
+// Every meminfo field we track with a bar each, history chart underneath
+fn draw_memory(f: &mut Frame, app: &App, area: Rect) {
+    let mem = match app.get_meminfo() {
+        Some(mem) => mem,
+        None => {
+            let block = Block::default().borders(Borders::ALL).title(" Memory 🧠 ");
+            let na = Paragraph::new("Memory info is not available")
+                .alignment(Alignment::Center)
+                .block(block);
+            f.render_widget(na, area);
+            return;
+        }
+    };
+
+    let rows = [
+        ("Used", mem.used(), Color::LightGreen),
+        ("Available", mem.available, Color::Gray),
+        ("Free", mem.free, Color::Gray),
+        ("Buffers", mem.buffers, Color::LightBlue),
+        ("Cached", mem.cached, Color::LightYellow),
+        ("Shared", mem.shared, Color::LightCyan),
+        ("Slab", mem.slab, Color::LightCyan),
+        ("Dirty", mem.dirty, Color::LightRed),
+    ];
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(rows.len() as u16 + 6),
+            Constraint::Min(6),
+        ])
+        .split(area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Memory {} total 🧠 ", mem.total));
+    let bar_width = block.inner(chunks[0]).width.saturating_sub(30) as usize;
+
+    let mut lines = vec![Line::from(memory_bar(mem, bar_width + 28)), Line::from("")];
+    for (name, value, color) in rows {
+        let percent = mem.percent(value);
+        let mut line = vec![Span::raw(format!("{:<10}{:>11} ", name, value.to_string()))];
+        line.extend(stacked_bar(&[(percent, color)], bar_width, "█", "░"));
+        line.push(Span::raw(format!("{:>6.1}%", percent)));
+        lines.push(Line::from(line));
+    }
+    // swap is out of its own total rather than ram
+    let mut line = vec![Span::raw(format!(
+        "{:<10}{:>11} ",
+        "Swap",
+        format!("{}/{}", mem.swap_used, mem.swap_total)
+    ))];
+    lines.push(Line::from(""));
+    line.extend(stacked_bar(
+        &[(mem.swap_percent(), Color::LightMagenta)],
+        bar_width,
+        "█",
+        "░",
+    ));
+    line.push(Span::raw(format!("{:>6.1}%", mem.swap_percent())));
+    lines.push(Line::from(line));
+    f.render_widget(Paragraph::new(lines).block(block), chunks[0]);
+
+    draw_memory_history(f, app, chunks[1]);
+}
+
+// Every sensor reading grouped under its chip
+fn draw_sensors(f: &mut Frame, app: &App, area: Rect) {
+    let sensors = app.get_sensor_rows();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Sensors ({}) 🌡️  ", sensors.len()));
+    if sensors.is_empty() {
+        let none = Paragraph::new("No hwmon or thermal zone sensors found")
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(none, area);
+        return;
+    }
+
+    let header = Row::new(vec!["Sensor", "Now", "Lowest", "Highest", "Limits"])
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+    // a bold row whenever the chip changes, so remember where the selection lands
+    let mut rows = Vec::new();
+    let mut selected = 0;
+    let mut chip = "";
+    for (i, sensor) in sensors.iter().enumerate() {
+        if sensor.chip != chip {
+            chip = sensor.chip;
+            rows.push(
+                Row::new(vec![chip.to_owned()])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            );
+        }
+        if i == app.get_sensor_selected() {
+            selected = rows.len();
+        }
+        let value = |v: f64| format!("{:.2} {}", v, sensor.unit);
+        let limits = [
+            ("min", sensor.min),
+            ("max", sensor.max),
+            ("crit", sensor.crit),
+        ]
+        .iter()
+        .filter_map(|(name, limit)| Some(format!("{} {:.1}", name, (*limit)?)))
+        .collect::<Vec<_>>()
+        .join("  ");
+        rows.push(Row::new(vec![
+            Line::from(format!("  {}", sensor.label)),
+            Line::from(Span::styled(
+                value(sensor.value),
+                Style::default().fg(limit_color(sensor)),
+            )),
+            Line::from(value(sensor.lowest)),
+            Line::from(value(sensor.highest)),
+            Line::from(limits),
+        ]));
+    }
+
+    let widths = [
+        Constraint::Min(20),
+        Constraint::Length(14),
+        Constraint::Length(14),
+        Constraint::Length(14),
+        Constraint::Min(30),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&widths)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default().with_selected(Some(selected));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+// One row per battery plus charge and draw history
+fn draw_battery(f: &mut Frame, app: &App, area: Rect) {
+    let batteries = app.get_batteries();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Batteries ({}) 🔋 ", batteries.len()));
+    if batteries.is_empty() {
+        let none = Paragraph::new("No batteries found in /sys/class/power_supply")
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(none, area);
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(batteries.len() as u16 + 4),
+            Constraint::Min(6),
+        ])
+        .split(area);
+
+    let header = Row::new(vec![
+        "Battery", "Status", "Charge", "Rate", "Time", "Energy", "Design", "Health", "Cycles",
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .bottom_margin(1);
+    let or_na = |v: Option<String>| v.unwrap_or_else(|| "NA".to_owned());
+    let rows: Vec<Row> = batteries
+        .iter()
+        .map(|b| {
+            let energy = match (b.energy_now, b.energy_full) {
+                (Some(now), Some(full)) => Some(format!("{:.1}/{:.1} Wh", now, full)),
+                _ => None,
+            };
+            let health = Line::from(match b.health() {
+                Some(h) => Span::styled(
+                    format!("{:.0}%", h),
+                    Style::default().fg(if h >= 80.0 {
+                        Color::LightGreen
+                    } else if h >= 50.0 {
+                        Color::LightYellow
+                    } else {
+                        Color::LightRed
+                    }),
+                ),
+                None => Span::raw("NA"),
+            });
+            Row::new(vec![
+                Line::from(b.name.clone()),
+                Line::from(b.status.label()),
+                Line::from(format!("{:.0}%", b.percent)),
+                Line::from(or_na(b.power.map(|w| format!("{:.1} W", w)))),
+                Line::from(or_na(b.time_left().map(battery::format_duration))),
+                Line::from(or_na(energy)),
+                Line::from(or_na(b.energy_design.map(|d| format!("{:.1} Wh", d)))),
+                health,
+                Line::from(or_na(b.cycles.map(|c| c.to_string()))),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(13),
+        Constraint::Length(7),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(16),
+        Constraint::Length(9),
+        Constraint::Length(7),
+        Constraint::Length(7),
+    ];
+    let table = Table::new(rows).header(header).widths(&widths).block(block);
+    f.render_widget(table, chunks[0]);
+
+    let charts = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    let charge = app.get_battery_points();
+    let plots = [Plot {
+        name: String::new(),
+        color: app.get_battery_color(),
+        points: &charge,
+    }];
+    let block = Block::default().borders(Borders::ALL).title(" Charge (%) ");
+    draw_history(
+        f,
+        app,
+        charts[0],
+        block,
+        HistoryChart::Battery,
+        &plots,
+        percent_axis(),
+        [0.0, 100.0],
+    );
+
+    let power = app.get_battery_power_points();
+    let top = power.iter().map(|p| p.1).fold(1.0, f64::max).ceil();
+    let plots = [Plot {
+        name: String::new(),
+        color: Color::LightCyan,
+        points: &power,
+    }];
+    let block = Block::default().borders(Borders::ALL).title(" Rate (W) ");
+    let axis = Axis::default().labels(vec![
+        Span::from("0"),
+        Span::from(format!("{:.0}", top / 2.0)),
+        Span::from(format!("{:.0}", top)),
+    ]);
+    draw_history(
+        f,
+        app,
+        charts[1],
+        block,
+        HistoryChart::Battery,
+        &plots,
+        axis,
+        [0.0, top],
+    );
+}
+
+// red past a limit, yellow when within 10% of the top one
+fn limit_color(sensor: &SensorRow) -> Color {
+    let high = sensor.max.or(sensor.crit);
+    if sensor.crit.is_some_and(|c| sensor.value >= c) {
+        Color::Red
+    } else if high.is_some_and(|h| sensor.value >= h)
+        || sensor.min.is_some_and(|m| sensor.value < m)
+    {
+        Color::LightRed
+    } else if high.is_some_and(|h| sensor.value >= h * 0.9) {
+        Color::LightYellow
+    } else {
+        Color::Reset
+    }
+}
+
+// Table of mounted filesystems with a usage bar per mount
+fn draw_mounts(f: &mut Frame, app: &App, area: Rect) {
+    let mounts = app.get_mounts();
+    let hidden = if app.show_pseudo {
+        ""
+    } else {
+        ", pseudo hidden"
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default())
+        .title(format!(" Filesystems ({}{}) 💾 ", mounts.len(), hidden));
+
+    let header = Row::new(vec![
+        "Mounted on",
+        "Device",
+        "Type",
+        "Size",
+        "Used",
+        "Avail",
+        "Use%",
+        "",
+        "Inodes",
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .bottom_margin(1);
+
+    let rows = mounts.iter().map(|m| {
+        Row::new(vec![
+            Line::from(m.mount_point.clone()),
+            Line::from(m.device.clone()),
+            Line::from(m.fs_type.clone()),
+            Line::from(m.total.to_string()),
+            Line::from(m.used.to_string()),
+            Line::from(m.avail.to_string()),
+            Line::from(format!("{:>3.0}%", m.percent())),
+            Line::from(usage_bar(app.config(), m.percent(), 12)),
+            Line::from(format!("{:>3.0}%", m.inode_percent())),
+        ])
+    });
+
+    let widths = [
+        Constraint::Min(12),
+        Constraint::Min(12),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(5),
+        Constraint::Length(12),
+        Constraint::Length(6),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&widths)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    // let ratatui work out the offset so the selected row stays on screen
+    let mut state = TableState::default().with_selected(if mounts.is_empty() {
+        None
+    } else {
+        Some(app.get_mount_selected())
+    });
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+// colours handed out to temperature sensors in order
+const SENSOR_COLORS: [Color; 8] = [
+    Color::LightBlue,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::Gray,
+    Color::Red,
+];
+
+// height of one device panel in the disk view
+const DISK_PANEL_HEIGHT: u16 = 7;
+
+// One panel per block device with its rates and read/write sparklines
+fn draw_disks(f: &mut Frame, app: &App, area: Rect) {
+    let disks = app.get_disks();
+    if disks.is_empty() {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Disk I/O 💽 ");
+        let waiting = Paragraph::new("Waiting for a second sample...")
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(waiting, area);
+        return;
+    }
+
+    // as many panels as fit, starting at the selected device
+    let fits = (area.height / DISK_PANEL_HEIGHT).max(1) as usize;
+    let shown = &disks[app.get_disk_selected()..];
+    let shown = &shown[..shown.len().min(fits)];
+    let mut constraints = vec![Constraint::Length(DISK_PANEL_HEIGHT); shown.len()];
+    constraints.push(Constraint::Min(0));
+    let panels = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(area);
+
+    for ((disk, history), panel) in shown.iter().zip(panels.iter()) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} 💽 ", disk.name));
+        let inner = block.inner(*panel);
+        f.render_widget(block, *panel);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .split(inner);
+
+        let mut stats = vec![
+            Span::styled("R ", Style::default().fg(Color::LightGreen)),
+            Span::raw(format!("{:<12}", rate(disk.read_bps))),
+            Span::styled("W ", Style::default().fg(Color::LightRed)),
+            Span::raw(format!("{:<12}", rate(disk.write_bps))),
+            Span::raw(format!(
+                "IOPS {:>6.0} r {:>6.0} w   busy ",
+                disk.read_iops, disk.write_iops
+            )),
+        ];
+        stats.extend(usage_bar(app.config(), disk.busy, 10));
+        stats.push(Span::raw(format!(" {:>3.0}%", disk.busy)));
+        f.render_widget(Paragraph::new(Line::from(stats)), rows[0]);
+
+        // read on the left, write on the right
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rows[1]);
+        for (name, points, color, half) in [
+            ("read", &history.read, Color::LightGreen, halves[0]),
+            ("write", &history.write, Color::LightRed, halves[1]),
+        ] {
+            let top = points.iter().map(|p| p.1).fold(1024.0, f64::max);
+            let plots = [Plot {
+                name: name.to_owned(),
+                color,
+                points,
+            }];
+            let axis = Axis::default().labels(vec![Span::from("0"), Span::from(rate(top))]);
+            draw_history(
+                f,
+                app,
+                half,
+                Block::default(),
+                HistoryChart::Disks,
+                &plots,
+                axis,
+                [0.0, top],
+            );
+        }
+    }
+}
+
+// Interface table on top, rolling rx/tx chart for the selected one below
+fn draw_network(f: &mut Frame, app: &App, area: Rect) {
+    let interfaces = app.get_interfaces();
+    let table_height = interfaces
+        .iter()
+        .map(|i| i.addrs.len().max(1) as u16)
+        .sum::<u16>()
+        + 4;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Max(table_height), Constraint::Min(8)])
+        .split(area);
+
+    // ++++++++ INTERFACE TABLE ++++++++ //
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Interfaces ({}) 🌐 ", interfaces.len()));
+    let header = Row::new(vec![
+        "Interface",
+        "Addresses",
+        "RX",
+        "TX",
+        "RX pkt/s",
+        "TX pkt/s",
+        "Errors rx/tx",
+        "Drops rx/tx",
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .bottom_margin(1);
+    let rows = interfaces.iter().map(|i| {
+        Row::new(vec![
+            Text::from(i.name.clone()),
+            Text::from(i.addrs.join("\n")),
+            Text::from(rate(i.rx_bps)),
+            Text::from(rate(i.tx_bps)),
+            Text::from(format!("{:.0}", i.rx_pps)),
+            Text::from(format!("{:.0}", i.tx_pps)),
+            Text::from(format!("{}/{}", i.rx_errors, i.tx_errors)),
+            Text::from(format!("{}/{}", i.rx_dropped, i.tx_dropped)),
+        ])
+        .height(i.addrs.len().max(1) as u16)
+    });
+    let widths = [
+        Constraint::Min(10),
+        Constraint::Min(28),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(13),
+        Constraint::Length(13),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&widths)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default().with_selected(if interfaces.is_empty() {
+        None
+    } else {
+        Some(app.get_net_selected())
+    });
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    // ++++++++ THROUGHPUT CHART ++++++++ //
+    let (name, history) = match app.get_net_history() {
+        Some((interface, history)) => (interface.name.as_str(), history),
+        None => {
+            let block = Block::default().borders(Borders::ALL).title(" Throughput ");
+            f.render_widget(block, chunks[1]);
+            return;
+        }
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} throughput ", name));
+
+    let top = history
+        .rx
+        .iter()
+        .chain(history.tx.iter())
+        .map(|p| p.1)
+        .fold(1024.0, f64::max)
+        * 1.1;
+
+    let plots = [
+        Plot {
+            name: "rx".to_owned(),
+            color: Color::LightGreen,
+            points: &history.rx,
+        },
+        Plot {
+            name: "tx".to_owned(),
+            color: Color::LightRed,
+            points: &history.tx,
+        },
+    ];
+    let axis = Axis::default().labels(vec![
+        Span::from("0"),
+        Span::from(rate(top / 2.0)),
+        Span::from(rate(top)),
+    ]);
+    draw_history(
+        f,
+        app,
+        chunks[1],
+        block,
+        HistoryChart::Network,
+        &plots,
+        axis,
+        [0.0, top],
+    );
+}
+
+// Filter line on top of a sortable process table
+fn draw_processes(f: &mut Frame, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(area);
+
+    // ++++++++ FILTER BOX ++++++++ //
+    let filter_style = if app.is_typing() {
+        Style::default().fg(Color::LightYellow)
+    } else {
         Style::default()
-            .fg(Color::DarkGray)
-            .bg(Color::LightYellow)
-            .add_modifier(Modifier::BOLD),
-    ))
-    .block(footer_block);
+    };
+    let cursor = if app.is_typing() { "█" } else { "" };
+    let filter = Paragraph::new(Line::from(vec![
+        Span::styled("/ ", Style::default().fg(Color::DarkGray)),
+        Span::raw(app.proc_filter.clone()),
+        Span::raw(cursor),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(filter_style)
+            .title(" Filter "),
+    );
+    f.render_widget(filter, chunks[0]);
 
-    // RENDER STUFF
-    f.render_widget(title, chunks[0]);
-    f.render_widget(footer, chunks[2]);
-    f.render_widget(load_block, loads_mem[0]);
-    f.render_widget(load_bars, load_bars_chunks[1]);
-    f.render_widget(memory, loads_mem[1]);
-    f.render_widget(temp_block, battery_temp_chunks[0]);
-    if unit == "C" {
-        f.render_widget(chart, temp_chunks[1]);
+    // ++++++++ PROCESS TABLE ++++++++ //
+    let processes = app.get_processes();
+    let view = if app.proc_tree { ", tree" } else { "" };
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        " Processes ({}{}) ⚙️  ",
+        processes.len(),
+        view
+    ));
+
+    // arrow on whichever column we sort by
+    let arrow = if app.proc_sort_desc { "▼" } else { "▲" };
+    let header = Row::new(
+        [
+            (ProcSort::Pid, "PID"),
+            (ProcSort::User, "USER"),
+            (ProcSort::Cpu, "CPU%"),
+            (ProcSort::Mem, "RSS"),
+            (ProcSort::State, "S"),
+            (ProcSort::Nice, "NI"),
+            (ProcSort::Command, "COMMAND"),
+        ]
+        .iter()
+        .map(|(sort, name)| {
+            if *sort == app.proc_sort {
+                format!("{}{}", name, arrow)
+            } else {
+                name.to_string()
+            }
+        }),
+    )
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows = processes.iter().map(|(depth, p)| {
+        let branch = if *depth == 0 {
+            String::new()
+        } else {
+            format!("{}└─ ", "   ".repeat(depth - 1))
+        };
+        Row::new(vec![
+            Line::from(p.pid.to_string()),
+            Line::from(p.user.clone()),
+            Line::from(Span::styled(
+                format!("{:.1}", p.cpu),
+                Style::default().fg(app.config().usage_color(p.cpu as f64)),
+            )),
+            Line::from(p.rss.to_string()),
+            Line::from(p.state.to_string()),
+            Line::from(p.nice.to_string()),
+            Line::from(format!("{}{}", branch, p.command)),
+        ])
+    });
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(6),
+        Constraint::Length(10),
+        Constraint::Length(2),
+        Constraint::Length(4),
+        Constraint::Percentage(100),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&widths)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default().with_selected(if processes.is_empty() {
+        None
     } else {
-        f.render_widget(chart_f, temp_chunks[1]);
+        Some(app.get_proc_selected())
+    });
+    f.render_stateful_widget(table, chunks[1], &mut state);
+}
+
+// One series on a history chart
+struct Plot<'a> {
+    name: String,
+    color: Color,
+    points: &'a [(f64, f64)],
+}
+
+// A history chart in the style picked for it. The chart styles plot every
+// series against the shared time axis, sparklines and bars give each series
+// a row of its newest samples scaled to the same bounds
+#[allow(clippy::too_many_arguments)]
+fn draw_history(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    block: Block,
+    chart: HistoryChart,
+    plots: &[Plot],
+    y_axis: Axis,
+    bounds: [f64; 2],
+) {
+    let style = app.graph(chart);
+    if style.over_time() {
+        let marker = match style {
+            app::GraphType::Braille => symbols::Marker::Braille,
+            _ => symbols::Marker::Dot,
+        };
+        let graph_type = match style {
+            app::GraphType::Scatter => GraphType::Scatter,
+            _ => GraphType::Line,
+        };
+        let datasets = plots
+            .iter()
+            .map(|plot| {
+                Dataset::default()
+                    .name(plot.name.clone())
+                    .marker(marker)
+                    .graph_type(graph_type)
+                    .style(Style::default().fg(plot.color))
+                    .data(plot.points)
+            })
+            .collect();
+        let chart = Chart::new(datasets)
+            .block(block)
+            .x_axis(time_axis(app))
+            .y_axis(y_axis.bounds(bounds));
+        f.render_widget(chart, area);
+        return;
+    }
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if plots.is_empty() {
+        return;
     }
-    f.render_widget(temp, temp_chunks[0]);
-    f.render_widget(battery_block, battery_temp_chunks[1]);
-    if app.is_on_ac_power() {
-        f.render_widget(ac_power, battery_recs[0]);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, plots.len() as u32); plots.len()])
+        .split(inner);
+    let [low, high] = bounds;
+    let scale =
+        |v: f64| ((v - low) / (high - low).max(f64::EPSILON) * 100.0).clamp(0.0, 100.0) as u64;
+    for (plot, row) in plots.iter().zip(rows.iter()) {
+        let color = Style::default().fg(plot.color);
+        if style == app::GraphType::Bar {
+            // two cells a bar with a gap after each
+            let values = tail(plot.points, row.width as usize / 3, scale);
+            let bars: Vec<Bar> = values
+                .iter()
+                .map(|v| Bar::default().value(*v).text_value(String::new()))
+                .collect();
+            let chart = BarChart::default()
+                .data(BarGroup::default().bars(&bars))
+                .bar_width(2)
+                .bar_gap(1)
+                .max(100)
+                .bar_style(color);
+            f.render_widget(chart, *row);
+        } else {
+            let values = tail(plot.points, row.width as usize, scale);
+            let spark = Sparkline::default().data(&values).max(100).style(color);
+            f.render_widget(spark, *row);
+        }
+        // say which row is which when there is more than one
+        if plots.len() > 1 && !plot.name.is_empty() {
+            let name = Rect { height: 1, ..*row };
+            f.render_widget(Paragraph::new(Span::styled(plot.name.clone(), color)), name);
+        }
+    }
+}
+
+// the chart 'g' would restyle stands out when there is more than one
+fn chart_border(app: &App, chart: HistoryChart) -> Style {
+    if app.chart_has_focus(chart) {
+        Style::default().fg(Color::LightCyan)
     } else {
-        f.render_widget(battery_percent, battery_recs[0]);
+        Style::default()
+    }
+}
+
+// 0 to 100 for the percent charts, bounds come from draw_history
+fn percent_axis() -> Axis<'static> {
+    Axis::default().labels(["0", "50", "100"].iter().cloned().map(Span::from).collect())
+}
+
+// Wall clock x axis for the history charts, they all share one window
+fn time_axis(app: &App) -> Axis<'static> {
+    let [first, last] = app.get_chart_window();
+    // seconds only matter on the short windows
+    let seconds = last - first <= 900.0;
+    Axis::default().bounds([first, last]).labels(
+        [first, (first + last) / 2.0, last]
+            .iter()
+            .map(|t| Span::from(history::clock(*t, seconds)))
+            .collect(),
+    )
+}
+
+// Y bounds around every value with a tenth of the range spare on each side,
+// at least `span` apart so a steady reading doesnt fill the whole chart
+fn fit_bounds(values: &[f64], span: f64) -> [f64; 2] {
+    let Some(low) = values.iter().copied().reduce(f64::min) else {
+        return [0.0, span];
+    };
+    let high = values.iter().copied().fold(low, f64::max);
+    let middle = (low + high) / 2.0;
+    let half = ((high - low) * 0.6).max(span / 2.0);
+    [(middle - half).floor(), (middle + half).ceil()]
+}
+
+// bottom, middle and top of an axis
+fn bound_labels(bounds: [f64; 2]) -> Vec<Span<'static>> {
+    [bounds[0], (bounds[0] + bounds[1]) / 2.0, bounds[1]]
+        .iter()
+        .map(|v| Span::from(format!("{:.0}", v)))
+        .collect()
+}
+
+// the newest `n` values scaled to whole numbers, sparklines draw from the
+// left so older ones would push them off
+fn tail(points: &[(f64, f64)], n: usize, scale: impl Fn(f64) -> u64) -> Vec<u64> {
+    points[points.len().saturating_sub(n)..]
+        .iter()
+        .map(|p| scale(p.1))
+        .collect()
+}
+
+// bytes per second in human units
+fn rate(bytes_per_sec: f64) -> String {
+    format!("{}/s", bytesize::ByteSize(bytes_per_sec as u64))
+}
+
+// text gauge that fits in a table cell
+fn usage_bar(config: &Config, percent: f64, width: usize) -> Vec<Span<'static>> {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f64).round() as usize;
+    vec![
+        Span::styled(
+            "█".repeat(filled),
+            Style::default().fg(config.usage_color(percent)),
+        ),
+        Span::styled(
+            "░".repeat(width - filled),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]
+}
+
+// the per core grid gets at most this many rows before the cells shrink
+const CORE_GRID_ROWS: usize = 8;
+const CORE_BAR_WIDTH: usize = 10;
+
+// Lay the cores out left to right, wrapping onto a new line when the next cell
+// wont fit in `width`. Each bar is stacked like htop: user, nice, system, interrupt.
+// Lots of cores in a narrow column first get shorter bars, then lose the
+// percent, so the grid stays within CORE_GRID_ROWS for as long as it can
+fn core_grid(cores: &[CoreLoad], width: u16) -> Vec<Line<'static>> {
+    // ids all as wide as the biggest so the columns line up past 100 cores
+    let id_width = cores.len().saturating_sub(1).to_string().len().max(2);
+    // "nn [bar] xx% " with the percent, "nn[bar] " without, gap included
+    let cell_width = |bar: usize, percent: bool| {
+        if percent {
+            id_width + bar + 9
+        } else {
+            id_width + bar + 3
+        }
+    };
+    let per_row = |bar, percent| (width as usize / cell_width(bar, percent)).max(1);
+    // widest bars first, with the percent before without
+    let (bar, percent) = (4..=CORE_BAR_WIDTH)
+        .rev()
+        .map(|bar| (bar, true))
+        .chain((2..=CORE_BAR_WIDTH).rev().map(|bar| (bar, false)))
+        .find(|(bar, percent)| cores.len().div_ceil(per_row(*bar, *percent)) <= CORE_GRID_ROWS)
+        .unwrap_or((2, false));
+    let per_row = per_row(bar, percent);
+
+    cores
+        .chunks(per_row)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let mut spans = Vec::new();
+            for (col, core) in chunk.iter().enumerate() {
+                let id = row * per_row + col;
+                let open = if percent { " [" } else { "[" };
+                spans.push(Span::raw(format!("{:>w$}{}", id, open, w = id_width)));
+
+                spans.extend(stacked_bar(
+                    &[
+                        (core.user as f64, Color::LightGreen),
+                        (core.nice as f64, Color::LightBlue),
+                        (core.system as f64, Color::LightRed),
+                        (core.interrupt as f64, Color::LightYellow),
+                    ],
+                    bar,
+                    "|",
+                    " ",
+                ));
+                if percent {
+                    spans.push(Span::raw(format!("]{:>4.0}% ", core.busy())));
+                } else {
+                    spans.push(Span::raw("] "));
+                }
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+// Bar made of coloured segments given in percent, htop style. The rest is
+// padded out with `empty` so every bar comes out `width` wide
+fn stacked_bar(
+    parts: &[(f64, Color)],
+    width: usize,
+    fill: &str,
+    empty: &str,
+) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    // cumulative so rounding never pushes us past the bar width
+    let mut filled = 0;
+    let mut total = 0.0;
+    for (value, color) in parts {
+        total += value.max(0.0);
+        let end = ((total / 100.0 * width as f64).round() as usize).min(width);
+        if end > filled {
+            spans.push(Span::styled(
+                fill.repeat(end - filled),
+                Style::default().fg(*color),
+            ));
+            filled = end;
+        }
     }
-    f.render_widget(battery_gauge, battery_recs[1]);
+    spans.push(Span::styled(
+        empty.repeat(width - filled),
+        Style::default().fg(Color::DarkGray),
+    ));
+    spans
 }
 
 /// # Usage
@@ -297,3 +1822,48 @@ fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{core_grid, fit_bounds, CORE_GRID_ROWS};
+    use sys_mon::loads::CoreLoad;
+
+    #[test]
+    fn bounds_fit_the_values_with_room_to_spare() {
+        assert_eq!(fit_bounds(&[], 10.0), [0.0, 10.0]);
+        // a steady reading still gets the whole span around it
+        assert_eq!(fit_bounds(&[50.0, 50.0], 10.0), [45.0, 55.0]);
+        // a tenth of the range spare on each side
+        assert_eq!(fit_bounds(&[40.0, 60.0, 45.0], 10.0), [38.0, 62.0]);
+        // rounded out to whole numbers
+        assert_eq!(fit_bounds(&[41.3, 47.9], 4.0), [40.0, 49.0]);
+    }
+
+    #[test]
+    fn core_grid_fits_a_half_width_column() {
+        let core = CoreLoad {
+            user: 40.0,
+            idle: 60.0,
+            ..CoreLoad::default()
+        };
+        // a few cores keep the full cells
+        let lines = core_grid(&[core; 4], 58);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].width(), 42);
+
+        // then shorter bars without the percent, past that it takes more rows
+        let width = 58;
+        for (count, rows) in [(32, CORE_GRID_ROWS), (64, CORE_GRID_ROWS), (128, 19)] {
+            let lines = core_grid(&vec![core; count], width);
+            assert_eq!(lines.len(), rows, "{} cores", count);
+            for line in &lines {
+                assert!(line.width() <= width as usize);
+                // ids are padded to the same width so the cells line up
+                let id = line.spans[0].content.trim_end_matches(['[', ' ']);
+                assert_eq!(id.len(), if count > 100 { 3 } else { 2 });
+            }
+            // every full row is the same width
+            assert_eq!(lines[0].width(), lines[lines.len() / 2].width());
+        }
+    }
+}
@@ -2,315 +2,1493 @@
 - This mod should handle the logic and state of the app
 */
 extern crate systemstat;
+use crate::config::Config;
 use crate::events::KeyActions;
-use bytesize::ByteSize;
-use std::sync::mpsc;
-use std::time::Duration;
-use std::{collections::HashMap, thread};
-use systemstat::{saturating_sub_bytes, Platform, System};
+use crate::history::{self, History};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use sys_mon::battery::{self, Battery, Status};
+use sys_mon::cpufreq::{self, FreqSummary};
+use sys_mon::disks::DiskIo;
+use sys_mon::memory::MemInfo;
+use sys_mon::mounts::MountInfo;
+use sys_mon::network::Interface;
+use sys_mon::procs::{self, Process};
+use sys_mon::psi::Psi;
+use sys_mon::rapl::{self, PowerZone};
+use sys_mon::sensors::TempSensor;
+use sys_mon::{Collected, CoreLoad, Loads, Schedule};
+use time::OffsetDateTime;
+use tokio::sync::watch;
 
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum Units {
+    #[value(name = "c", alias = "celsius")]
     Celcius,
+    #[value(name = "f", alias = "fahrenheit")]
     Fahrenheit,
 }
 
+impl Units {
+    // a celsius reading in this unit
+    pub fn convert(self, celsius: f32) -> f32 {
+        match self {
+            Units::Celcius => celsius,
+            Units::Fahrenheit => celsius * (9.0 / 5.0) + 32.0,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Units::Celcius => "C",
+            Units::Fahrenheit => "F",
+        }
+    }
+}
+
+// the boxes on the overview page, any of them can be left out
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Panel {
+    Load,
+    Cores,
+    Psi,
+    Memory,
+    Temp,
+    MemoryHistory,
+    Battery,
+    Power,
+}
+
+// How a history chart is drawn, 'g' cycles the one on screen
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum GraphType {
-    SparkLine,
+    // dots joined up
+    Line,
+    // just the samples
     Scatter,
+    // joined up at braille resolution, the default
+    Braille,
+    // newest samples as a sparkline per series
+    #[value(name = "sparkline")]
+    SparkLine,
+    Bar,
 }
-pub enum State {
-    Run,
-    Quit,
+
+impl GraphType {
+    fn next(self) -> Self {
+        match self {
+            GraphType::Line => GraphType::Scatter,
+            GraphType::Scatter => GraphType::Braille,
+            GraphType::Braille => GraphType::SparkLine,
+            GraphType::SparkLine => GraphType::Bar,
+            GraphType::Bar => GraphType::Line,
+        }
+    }
+
+    // drawn against the time axis rather than as the newest samples in a row
+    pub fn over_time(self) -> bool {
+        matches!(
+            self,
+            GraphType::Line | GraphType::Scatter | GraphType::Braille
+        )
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GraphType::Line => "line",
+            GraphType::Scatter => "scatter",
+            GraphType::Braille => "braille",
+            GraphType::SparkLine => "sparkline",
+            GraphType::Bar => "bar",
+        }
+    }
 }
 
-// Poller to check syst monitor
-#[derive(Default)]
-pub struct Poller {}
-impl Poller {
-    pub fn new() -> Self {
-        Poller::default()
-    }
-
-    pub fn sys_mon(&mut self, tx: mpsc::SyncSender<Loads>) {
-        let sys = System::new();
-        //set cpu temp
-        loop {
-            let mut loads = Loads::new();
-
-            // set load
-            match sys.cpu_load_aggregate() {
-                Ok(cpu) => {
-                    thread::sleep(Duration::from_millis(250));
-                    let cpu = cpu.done().unwrap();
-                    loads.user = Some(cpu.user * 100.0);
-                    loads.nice = Some(cpu.nice * 100.0);
-                    loads.system = Some(cpu.system * 100.0);
-                    loads.interrupt = Some(cpu.interrupt * 100.0);
-                    loads.idle = Some(cpu.idle * 100.0);
-                }
-                Err(_) => {
-                    // set to None if we error
-                    loads.user = None;
-                    loads.nice = None;
-                    loads.system = None;
-                    loads.interrupt = None;
-                    loads.idle = None;
-                }
-            }
+// The history charts a style is kept for. Memory is the same chart on the
+// overview and the memory view, battery covers both charge and rate, disks
+// covers every device's read and write
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum HistoryChart {
+    Temp,
+    Memory,
+    Network,
+    Battery,
+    Disks,
+    Power,
+}
 
-            //set cpu temp
-            match sys.cpu_temp() {
-                Ok(cpu_temp) => {
-                    loads.temp = Some(cpu_temp);
-                }
-                Err(_) => {
-                    loads.temp = None;
-                }
-            }
+impl HistoryChart {
+    pub fn name(self) -> &'static str {
+        match self {
+            HistoryChart::Temp => "temp",
+            HistoryChart::Memory => "memory",
+            HistoryChart::Network => "network",
+            HistoryChart::Battery => "battery",
+            HistoryChart::Disks => "disks",
+            HistoryChart::Power => "power",
+        }
+    }
+}
+// Which page of the monitor is on screen
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Overview,
+    Mounts,
+    Disks,
+    Network,
+    Processes,
+    Memory,
+    Sensors,
+    Battery,
+}
 
-            // set battery temp
-            match sys.battery_life() {
-                Ok(battery) => {
-                    let h = (battery.remaining_time.as_secs() / 3600) as u32;
-                    let m = (battery.remaining_time.as_secs() % 60) as u32;
-                    loads.battery = Some((battery.remaining_capacity * 100.0) as u8);
-                    loads.battery_time = Some((h, m));
-
-                    // set the battery color
-                    if let Some(x) = &loads.battery {
-                        if *x >= 65 {
-                            loads.battery_color = ratatui::style::Color::LightGreen;
-                        } else if *x < 65 && *x >= 25 {
-                            loads.battery_color = ratatui::style::Color::LightYellow;
-                        } else {
-                            loads.battery_color = ratatui::style::Color::LightRed;
-                        }
-                    } else {
-                        loads.battery_color = ratatui::style::Color::Red;
-                    }
-                }
-                Err(_) => loads.battery = None,
-            }
+// Something the user asked to do to a process, waiting on the confirmation popup
+pub enum ProcAction {
+    Signal {
+        pid: u32,
+        name: String,
+        signal: libc::c_int,
+        label: &'static str,
+    },
+    Renice {
+        pid: u32,
+        name: String,
+        nice: i32,
+    },
+}
 
-            // Check if AC Power is on
-            match sys.on_ac_power() {
-                Ok(p) => {
-                    loads.ac_power = Some(p);
-                }
-                Err(_) => loads.ac_power = None,
-            };
+// how long a status message stays in the footer
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
 
-            // set memory usage
-            match sys.memory() {
-                Ok(mem) => {
-                    loads.mem = Some((saturating_sub_bytes(mem.total, mem.free), mem.total));
-                }
-                Err(_) => loads.mem = None,
-            };
+// column the process table is sorted by
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProcSort {
+    Pid,
+    User,
+    Cpu,
+    Mem,
+    State,
+    Nice,
+    Command,
+}
 
-            // Send results
-            let res = tx.send(loads);
-            if res.is_ok() {
-            } else {
-                break;
-            }
+impl ProcSort {
+    fn next(self) -> Self {
+        match self {
+            ProcSort::Pid => ProcSort::User,
+            ProcSort::User => ProcSort::Cpu,
+            ProcSort::Cpu => ProcSort::Mem,
+            ProcSort::Mem => ProcSort::State,
+            ProcSort::State => ProcSort::Nice,
+            ProcSort::Nice => ProcSort::Command,
+            ProcSort::Command => ProcSort::Pid,
         }
     }
 }
 
-pub struct Loads {
-    nice: Option<f32>,
-    user: Option<f32>,
-    system: Option<f32>,
-    interrupt: Option<f32>,
-    idle: Option<f32>,
-    temp: Option<f32>,
-    battery: Option<u8>,
-    ac_power: Option<bool>,
-    mem: Option<(ByteSize, ByteSize)>,
-    battery_time: Option<(u32, u32)>,
-    battery_color: ratatui::style::Color,
+// One line of the sensors view, temperatures and hwmon readings alike
+pub struct SensorRow<'a> {
+    pub chip: &'a str,
+    pub label: &'a str,
+    pub unit: &'static str,
+    pub value: f64,
+    // lowest and highest value since we started
+    pub lowest: f64,
+    pub highest: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub crit: Option<f64>,
+}
+
+// "250ms" under a second, "2s" from there up
+pub fn format_interval(interval: Duration) -> String {
+    match interval.as_millis() {
+        ms if ms < 1000 => format!("{}ms", ms),
+        ms => format!("{}s", ms as f64 / 1000.0),
+    }
+}
+
+// "3d 4h 5m", days only once there are some
+fn format_uptime(up: Duration) -> String {
+    let secs = up.as_secs();
+    let (d, h, m) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if d > 0 {
+        format!("{}d {}h {}m", d, h, m)
+    } else {
+        format!("{}h {}m", h, m)
+    }
+}
+
+// boot time in UTC, we dont know the local offset safely from a threaded program
+fn format_boot_time(t: OffsetDateTime) -> String {
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02} UTC",
+        t.year(),
+        t.month() as u8,
+        t.day(),
+        t.hour(),
+        t.minute()
+    )
+}
+
+// The signal a process key sends, stop turns into continue for a process
+// that is already stopped
+fn signal_for(key: KeyActions, state: char) -> Option<(libc::c_int, &'static str)> {
+    match key {
+        KeyActions::Terminate => Some((libc::SIGTERM, "SIGTERM")),
+        KeyActions::Kill => Some((libc::SIGKILL, "SIGKILL")),
+        KeyActions::StopContinue if state == 'T' => Some((libc::SIGCONT, "SIGCONT")),
+        KeyActions::StopContinue => Some((libc::SIGSTOP, "SIGSTOP")),
+        _ => None,
+    }
+}
+
+// up means more priority which is a lower nice value
+fn step_nice(nice: i32, key: KeyActions) -> i32 {
+    let step = match key {
+        KeyActions::Up => -1,
+        KeyActions::Down => 1,
+        _ => 0,
+    };
+    (nice + step).clamp(-20, 19)
 }
 
-impl Loads {
-    pub fn new() -> Loads {
-        Loads {
-            nice: None,
-            user: None,
-            system: None,
-            interrupt: None,
-            idle: None,
-            temp: None,
-            battery: None,
-            ac_power: None,
-            mem: None,
-            battery_time: None,
-            battery_color: ratatui::style::Color::Red,
+// "disk/sda/read" -> "sda", for keys under a per device prefix
+fn device<'a>(key: &'a str, kind: &str) -> Option<&'a str> {
+    let rest = key.strip_prefix(kind)?.strip_prefix('/')?;
+    rest.rsplit_once('/').map(|(name, _)| name)
+}
+
+// rx and tx throughput in bytes/s against the time they were sampled
+pub struct NetHistory {
+    pub rx: Vec<(f64, f64)>,
+    pub tx: Vec<(f64, f64)>,
+}
+
+// read and write throughput history for one block device
+pub struct DiskHistory {
+    pub read: Vec<(f64, f64)>,
+    pub write: Vec<(f64, f64)>,
+}
+
+// How far back the history charts reach, the same for all of them
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Minute,
+    FiveMinutes,
+    FifteenMinutes,
+    Hour,
+    All,
+}
+
+// the windows '[' and ']' step between, narrowest first
+const WINDOWS: [Window; 5] = [
+    Window::Minute,
+    Window::FiveMinutes,
+    Window::FifteenMinutes,
+    Window::Hour,
+    Window::All,
+];
+
+impl Window {
+    // None for everything we still have
+    pub fn secs(self) -> Option<f64> {
+        match self {
+            Window::Minute => Some(60.0),
+            Window::FiveMinutes => Some(300.0),
+            Window::FifteenMinutes => Some(900.0),
+            Window::Hour => Some(3600.0),
+            Window::All => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Window::Minute => "1m",
+            Window::FiveMinutes => "5m",
+            Window::FifteenMinutes => "15m",
+            Window::Hour => "1h",
+            Window::All => "all",
         }
     }
 }
 
+// what the temperature chart shows, frequency is plotted as a percent of max when overlaid
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FreqChart {
+    Off,
+    Overlay,
+    Only,
+}
+
+pub enum State {
+    Run,
+    Quit,
+}
+
+// the sample interval steps '+' and '-' move between, in milliseconds. The
+// config and command line are held to the same range
+pub const INTERVALS: [u64; 8] = [100, 250, 500, 1000, 2000, 5000, 10000, 30000];
+
 //this struct should handlle the state of the app
 pub struct App {
     pub load: Loads,
     pub units: Units,
     pub state: State,
-    pub graph: GraphType,
-    temp_vec: Vec<(f64, f64)>,
-    temp_vec_f: Vec<(f64, f64)>,
-    reciever: Option<mpsc::Receiver<Loads>>,
-    event_handler: Option<mpsc::Receiver<Option<KeyActions>>>,
+    pub view: View,
+    pub show_pseudo: bool,
+    // what the config file and the command line asked for
+    config: Config,
+    mount_selected: usize,
+    disk_selected: usize,
+    net_selected: usize,
+    // every chart's data, by metric
+    history: History,
+    pub window: Window,
+    // when the last sample came in, the right edge of every chart
+    updated: f64,
+    pub proc_sort: ProcSort,
+    pub proc_sort_desc: bool,
+    pub proc_tree: bool,
+    pub proc_filter: String,
+    // the selected process, followed through sorting, filtering and new
+    // samples. The row is where it was last seen, for when it exits
+    proc_selected: Option<u32>,
+    proc_row: usize,
+    pub popup: Option<ProcAction>,
+    // last result of acting on a process, true if it went wrong
+    status: Option<(String, bool, Instant)>,
+    // a filter is being typed, keys go in as text
+    typing: bool,
+    // chart styles picked since main last wrote them to the config file
+    graph: Vec<(HistoryChart, GraphType)>,
+    // picked with 'h' when a view has more than one history chart
+    chart_focus: Option<HistoryChart>,
+    // lowest and highest value seen per sensor id, temps in celsius
+    sensor_extremes: HashMap<String, (f64, f64)>,
+    sensor_selected: usize,
+    pub freq_chart: FreqChart,
+    // sample interval and collector intervals, the poller watches it for changes
+    schedule: watch::Sender<Schedule>,
 }
 
 impl App {
-    pub fn new() -> App {
+    pub fn new(config: Config) -> App {
         App {
             load: Loads::new(),
-            units: Units::Celcius,
+            units: config.units,
             state: State::Run,
-            reciever: None,
-            graph: GraphType::Scatter,
-            temp_vec: Vec::new(),
-            temp_vec_f: Vec::new(),
-            event_handler: None,
+            view: View::Overview,
+            show_pseudo: false,
+            mount_selected: 0,
+            disk_selected: 0,
+            net_selected: 0,
+            proc_sort: ProcSort::Cpu,
+            proc_sort_desc: true,
+            proc_tree: false,
+            proc_filter: String::new(),
+            proc_selected: None,
+            proc_row: 0,
+            popup: None,
+            status: None,
+            typing: false,
+            schedule: watch::channel(config.schedule()).0,
+            history: History::new(config.history),
+            window: Window::FiveMinutes,
+            updated: history::now(),
+            graph: Vec::new(),
+            chart_focus: None,
+            sensor_extremes: HashMap::new(),
+            sensor_selected: 0,
+            freq_chart: FreqChart::Off,
+            config,
         }
     }
-    pub fn set_event_handleer(&mut self, rx: mpsc::Receiver<Option<KeyActions>>) {
-        self.event_handler = Some(rx);
+    // hand this to the poller so it hears about interval changes
+    pub fn schedule_watch(&self) -> watch::Receiver<Schedule> {
+        self.schedule.subscribe()
+    }
+    pub fn get_interval(&self) -> Duration {
+        self.schedule.borrow().interval
+    }
+    pub fn config(&self) -> &Config {
+        &self.config
     }
-    pub fn set_reciever(&mut self, rx: mpsc::Receiver<Loads>) {
-        self.reciever = Some(rx);
+    // how often the screen is redrawn when nothing is pressed
+    pub fn render_rate(&self) -> Duration {
+        self.config.render_rate
+    }
+    // The config file changed. A broken one leaves everything as it was
+    pub fn reload_config(&mut self, config: Result<Config, String>) {
+        let config = match config {
+            Ok(config) => config,
+            Err(e) => return self.set_status(e, true),
+        };
+        // TAB wins until the file's unit itself changes
+        if config.units != self.config.units {
+            self.units = config.units;
+        }
+        // the same goes for an interval picked with +/-
+        let mut schedule = config.schedule();
+        if config.interval == self.config.interval {
+            schedule.interval = self.get_interval();
+        }
+        if schedule != *self.schedule.borrow() {
+            self.schedule.send_replace(schedule);
+        }
+        self.history.set_len(config.history);
+        self.config = config;
+        self.set_status("config reloaded".to_owned(), false);
+    }
+    // one step along INTERVALS, slower is a longer interval
+    fn step_interval(&mut self, slower: bool) {
+        let now = self.get_interval().as_millis() as u64;
+        let next = if slower {
+            INTERVALS.iter().find(|&&i| i > now).copied()
+        } else {
+            INTERVALS.iter().rev().find(|&&i| i < now).copied()
+        };
+        if let Some(next) = next {
+            self.schedule
+                .send_modify(|s| s.interval = Duration::from_millis(next));
+        }
+        self.set_status(
+            format!("sampling every {}", format_interval(self.get_interval())),
+            false,
+        );
+    }
+    // how this chart is drawn right now
+    pub fn graph(&self, chart: HistoryChart) -> GraphType {
+        self.config.chart_style(chart)
+    }
+    // the history charts on screen, in the order focus moves through them
+    fn view_charts(&self) -> Vec<HistoryChart> {
+        match self.view {
+            View::Overview => [
+                (Panel::Temp, HistoryChart::Temp),
+                (Panel::MemoryHistory, HistoryChart::Memory),
+                (Panel::Power, HistoryChart::Power),
+            ]
+            .iter()
+            .filter(|(panel, _)| self.shows(*panel))
+            .map(|(_, chart)| *chart)
+            .collect(),
+            View::Memory => vec![HistoryChart::Memory],
+            View::Network => vec![HistoryChart::Network],
+            View::Battery => vec![HistoryChart::Battery],
+            View::Disks => vec![HistoryChart::Disks],
+            View::Mounts | View::Processes | View::Sensors => Vec::new(),
+        }
+    }
+    // the chart 'g' restyles, the first one on the view until another is picked
+    pub fn focused_chart(&self) -> Option<HistoryChart> {
+        let charts = self.view_charts();
+        match self.chart_focus {
+            Some(chart) if charts.contains(&chart) => Some(chart),
+            _ => charts.first().copied(),
+        }
+    }
+    // true when the view has a choice of charts and this one has the focus
+    pub fn chart_has_focus(&self, chart: HistoryChart) -> bool {
+        self.view_charts().len() > 1 && self.focused_chart() == Some(chart)
+    }
+    fn focus_next_chart(&mut self) {
+        let charts = self.view_charts();
+        let Some(at) = self
+            .focused_chart()
+            .and_then(|c| charts.iter().position(|&x| x == c))
+        else {
+            return self.set_status("no history chart on this view".to_owned(), true);
+        };
+        let chart = charts[(at + 1) % charts.len()];
+        self.chart_focus = Some(chart);
+        self.set_status(format!("{} chart picked", chart.name()), false);
+    }
+    // next style for the focused chart, each one goes on from its own style
+    fn cycle_graph(&mut self) {
+        let Some(chart) = self.focused_chart() else {
+            return self.set_status("no history chart on this view".to_owned(), true);
+        };
+        let style = self.graph(chart).next();
+        self.config.charts.styles.insert(chart, style);
+        self.graph.push((chart, style));
+        self.set_status(format!("{} chart: {}", chart.name(), style.name()), false);
+    }
+    // the chart styles to save, if any were picked
+    pub fn take_graph(&mut self) -> Vec<(HistoryChart, GraphType)> {
+        std::mem::take(&mut self.graph)
+    }
+    fn step_window(&mut self, wider: bool) {
+        let at = WINDOWS.iter().position(|w| *w == self.window).unwrap_or(0);
+        let next = if wider {
+            (at + 1).min(WINDOWS.len() - 1)
+        } else {
+            at.saturating_sub(1)
+        };
+        self.window = WINDOWS[next];
+        self.set_status(format!("charts show {}", self.window.label()), false);
+    }
+    // x bounds every history chart shares, seconds since the epoch
+    pub fn get_chart_window(&self) -> [f64; 2] {
+        let first = match self.window.secs() {
+            Some(secs) => self.updated - secs,
+            None => self.history.oldest().unwrap_or(self.updated),
+        };
+        [first, self.updated.max(first + 1.0)]
+    }
+    // one series cut down to the chart window
+    fn chart_points(&self, key: &str) -> Vec<(f64, f64)> {
+        self.history.since(key, self.get_chart_window()[0])
+    }
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+    // whether the overview draws this panel
+    pub fn shows(&self, panel: Panel) -> bool {
+        self.config.panels.contains(&panel)
     }
 }
 
 impl App {
     pub fn get_temp(&self) -> f32 {
-        match self.units {
-            Units::Celcius => self.load.temp.unwrap_or(9999.9999),
-            Units::Fahrenheit => self.load.temp.unwrap_or(9999.9999) * (9.0 / 5.0) + 32.0,
+        self.units
+            .convert(self.load.cpu_temp().unwrap_or(9999.9999))
+    }
+
+    // cpu temperature over time in the chosen unit
+    pub fn get_temp_points(&self) -> Vec<(f64, f64)> {
+        self.temp_points("temp")
+    }
+
+    // sensors with their history, in the order the poller found them
+    pub fn get_sensor_points(&self) -> Vec<(&TempSensor, Vec<(f64, f64)>)> {
+        self.load
+            .temps()
+            .iter()
+            .map(|s| (s, self.temp_points(&format!("sensor/{}", s.id))))
+            .filter(|(_, points)| !points.is_empty())
+            .collect()
+    }
+
+    // history is kept in celsius, charts show whatever TAB picked
+    fn temp_points(&self, key: &str) -> Vec<(f64, f64)> {
+        let mut points = self.chart_points(key);
+        for point in &mut points {
+            point.1 = self.units.convert(point.1 as f32) as f64;
         }
+        points
     }
 
-    // returns a slice of our vector of temp points....
-    pub fn get_temp_points<'a>(&'a self) -> &'a [(f64, f64)] {
-        match self.units {
-            Units::Celcius => {
-                let slice = &self.temp_vec[0..(self.temp_vec.len())];
-                slice.try_into().unwrap()
+    // The lowest hwmon max and crit among the charted sensors, the first
+    // ones we would hit. None for both when the config turns the lines off
+    pub fn get_temp_limits(&self, plotted: &[&TempSensor]) -> (Option<f64>, Option<f64>) {
+        if !self.config.charts.limits {
+            return (None, None);
+        }
+        let lowest = |limit: fn(&TempSensor) -> Option<f32>| {
+            plotted
+                .iter()
+                .filter_map(|s| limit(s))
+                .reduce(f32::min)
+                .map(|c| self.units.convert(c) as f64)
+        };
+        (lowest(|s| s.max), lowest(|s| s.crit))
+    }
+
+    pub fn get_psi(&self) -> Option<&Psi> {
+        self.load.psi()
+    }
+
+    // average/max frequency, governor and throttle count across cores
+    pub fn get_freq_summary(&self) -> Option<FreqSummary> {
+        cpufreq::summarize(self.load.freqs())
+    }
+
+    pub fn get_freq_points(&self) -> Vec<(f64, f64)> {
+        self.chart_points("freq")
+    }
+
+    // Every sensor grouped by chip for the sensors view, temps in the chosen unit
+    pub fn get_sensor_rows(&self) -> Vec<SensorRow<'_>> {
+        let temp = |c: f32| self.units.convert(c) as f64;
+        let unit = self.units.symbol();
+
+        let mut rows: Vec<SensorRow> = self
+            .load
+            .temps()
+            .iter()
+            .map(|s| {
+                let (lo, hi) = self
+                    .sensor_extremes
+                    .get(&s.id)
+                    .copied()
+                    .unwrap_or((s.celsius as f64, s.celsius as f64));
+                SensorRow {
+                    chip: &s.chip,
+                    label: &s.label,
+                    unit,
+                    value: temp(s.celsius),
+                    lowest: temp(lo as f32),
+                    highest: temp(hi as f32),
+                    min: None,
+                    max: s.max.map(temp),
+                    crit: s.crit.map(temp),
+                }
+            })
+            .collect();
+        rows.extend(self.load.readings().iter().map(|r| {
+            let (lowest, highest) = self
+                .sensor_extremes
+                .get(&r.id)
+                .copied()
+                .unwrap_or((r.value, r.value));
+            SensorRow {
+                chip: &r.chip,
+                label: &r.label,
+                unit: r.kind.unit(),
+                value: r.value,
+                lowest,
+                highest,
+                min: r.min,
+                max: r.max,
+                crit: r.crit,
             }
-            Units::Fahrenheit => {
-                let slice = &self.temp_vec[0..(self.temp_vec.len())];
-                slice.try_into().unwrap()
+        }));
+
+        // keep chips together, in the order we first saw them
+        let mut chips: Vec<&str> = Vec::new();
+        for row in &rows {
+            if !chips.contains(&row.chip) {
+                chips.push(row.chip);
             }
         }
+        rows.sort_by_key(|row| chips.iter().position(|c| *c == row.chip));
+        rows
+    }
+
+    // first row shown in the sensors view
+    pub fn get_sensor_selected(&self) -> usize {
+        let len = self.load.temps().len() + self.load.readings().len();
+        self.sensor_selected.min(len.saturating_sub(1))
     }
 
     pub fn get_battery_color(&self) -> ratatui::style::Color {
-        self.load.battery_color
+        match self.load.power().total() {
+            Some(b) => self.config.battery_color(b.percent as f64),
+            None => ratatui::style::Color::Red,
+        }
     }
     // Get memory return tuple of used, total maybe string is fine
     pub fn get_mem(&self) -> (String, String) {
-        match self.load.mem {
-            Some(mem) => (mem.0.to_string(), mem.1.to_string()),
+        match self.load.memory() {
+            Some(mem) => (mem.used().to_string(), mem.total.to_string()),
             None => ("NA".to_owned(), "NA".to_owned()),
         }
     }
+
+    // full meminfo breakdown for the gauges and the memory view
+    pub fn get_meminfo(&self) -> Option<&MemInfo> {
+        self.load.memory()
+    }
+
+    // memory history in percent
+    pub fn get_mem_points(&self) -> Vec<(f64, f64)> {
+        self.chart_points("mem")
+    }
+
+    // swap history in percent
+    pub fn get_swap_points(&self) -> Vec<(f64, f64)> {
+        self.chart_points("swap")
+    }
     // gets battery as u8
     pub fn get_battery_left(&self) -> u8 {
-        if let Some(p) = self.load.battery {
-            p
-        } else {
-            0
-        }
+        self.load
+            .power()
+            .total()
+            .map(|b| b.percent as u8)
+            .unwrap_or_default()
     }
     pub fn is_on_ac_power(&self) -> bool {
-        match self.load.ac_power {
-            Some(p) => p,
-            None => false,
-        }
+        self.load.power().ac_power.unwrap_or_default()
+    }
+
+    pub fn get_batteries(&self) -> &[Battery] {
+        &self.load.power().batteries
     }
 
     //Get battery time left
     pub fn get_battery_time(&self) -> String {
-        if let Some((h, m)) = self.load.battery_time {
-            format!("Time Remaining: {}h {}m", h, m)
-        } else {
-            "Err".to_owned()
+        let battery = match self.load.power().total() {
+            Some(battery) => battery,
+            None => return "No battery".to_owned(),
+        };
+        let watts = match battery.power {
+            Some(w) => format!(" at {:.1} W", w),
+            None => String::new(),
+        };
+        match (battery.status, battery.time_left()) {
+            (Status::Discharging, Some(t)) => {
+                format!("{} to empty{}", battery::format_duration(t), watts)
+            }
+            (Status::Charging, Some(t)) => {
+                format!("{} to full{}", battery::format_duration(t), watts)
+            }
+            (status, _) => format!("{}{}", status.label(), watts),
         }
     }
 
+    // RAPL zones, or why we couldnt read them
+    pub fn get_power_zones(&self) -> Result<&[PowerZone], &str> {
+        match self.load.errors().get(rapl::NAME) {
+            Some(why) => Err(why),
+            None => Ok(self.load.rapl()),
+        }
+    }
+
+    // package draw history in watts
+    pub fn get_power_history(&self) -> Vec<(f64, f64)> {
+        self.chart_points("rapl")
+    }
+
+    // charge history in percent
+    pub fn get_battery_points(&self) -> Vec<(f64, f64)> {
+        self.chart_points("battery")
+    }
+
+    // draw history in watts
+    pub fn get_battery_power_points(&self) -> Vec<(f64, f64)> {
+        self.chart_points("battery/power")
+    }
+
     // get hashmap for temp things
     pub fn get_load(&self) -> HashMap<String, f32> {
         let mut loads = HashMap::new();
 
-        loads.insert("user".to_string(), self.load.user.unwrap_or(9999.99));
-        loads.insert("nice".to_string(), self.load.nice.unwrap_or(9999.99));
-        loads.insert("system".to_string(), self.load.system.unwrap_or(9999.99));
+        loads.insert(
+            "user".to_string(),
+            self.load.cpu().map_or(9999.99, |c| c.user),
+        );
+        loads.insert(
+            "nice".to_string(),
+            self.load.cpu().map_or(9999.99, |c| c.nice),
+        );
+        loads.insert(
+            "system".to_string(),
+            self.load.cpu().map_or(9999.99, |c| c.system),
+        );
         loads.insert(
             "interrupt".to_string(),
-            self.load.interrupt.unwrap_or(9999.99),
+            self.load.cpu().map_or(9999.99, |c| c.interrupt),
+        );
+        loads.insert(
+            "idle".to_string(),
+            self.load.cpu().map_or(9999.99, |c| c.idle),
         );
-        loads.insert("idle".to_string(), self.load.idle.unwrap_or(9999.99));
 
         loads
     }
 
-    pub fn poll(&mut self) {
-        // set values
-        match &self.reciever {
-            //pull load off channel
-            Some(rx) => {
-                if let Ok(loads) = rx.recv_timeout(Duration::from_millis(250)) {
-                    // Temp Vector for chart. Going to limit data points to 10k so we dont just eat memory
-                    if self.temp_vec.len() > 10000 {
-                        self.temp_vec = Vec::new();
-                    }
-                    // push new value on
-                    self.temp_vec
-                        .push((self.temp_vec.len() as f64, loads.temp.unwrap_or(0.0) as f64));
-                    self.temp_vec_f.push((
-                        self.temp_vec_f.len() as f64,
-                        loads.temp.unwrap_or(0.0) as f64,
-                    ));
-                    // Replace Loads struct
-                    self.load = loads;
+    // collectors whose last run failed, and why
+    pub fn get_collector_errors(&self) -> &BTreeMap<String, String> {
+        self.load.errors()
+    }
+
+    // hostname and kernel version for the title
+    pub fn get_host(&self) -> (String, String) {
+        match self.load.host() {
+            Some((name, kernel)) => (name.to_owned(), kernel.to_owned()),
+            None => ("NA".to_owned(), "NA".to_owned()),
+        }
+    }
+
+    // 1, 5 and 15 minute load averages
+    pub fn get_load_average(&self) -> String {
+        match self.load.load_average() {
+            Some((one, five, fifteen)) => format!("{:.2} {:.2} {:.2}", one, five, fifteen),
+            None => "NA".to_owned(),
+        }
+    }
+
+    pub fn get_uptime(&self) -> String {
+        match self.load.uptime() {
+            Some(up) => format_uptime(up),
+            None => "NA".to_owned(),
+        }
+    }
+
+    pub fn get_boot_time(&self) -> String {
+        match self.load.boot_time() {
+            Some(t) => format_boot_time(t),
+            None => "NA".to_owned(),
+        }
+    }
+
+    // per core loads, empty if the platform couldnt give us any
+    pub fn get_core_loads(&self) -> &[CoreLoad] {
+        self.load.cores()
+    }
+
+    // mounts for the table, pseudo filesystems only if they are toggled on
+    pub fn get_mounts(&self) -> Vec<&MountInfo> {
+        match self.load.mounts() {
+            Some(mounts) => mounts
+                .iter()
+                .filter(|m| self.show_pseudo || !m.pseudo)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // selected row in the mounts table, kept inside the current list
+    pub fn get_mount_selected(&self) -> usize {
+        self.mount_selected
+            .min(self.get_mounts().len().saturating_sub(1))
+    }
+
+    // block devices with their throughput history
+    pub fn get_disks(&self) -> Vec<(&DiskIo, DiskHistory)> {
+        match self.load.disks() {
+            Some(disks) => disks
+                .iter()
+                .map(|d| {
+                    let history = DiskHistory {
+                        read: self.chart_points(&format!("disk/{}/read", d.name)),
+                        write: self.chart_points(&format!("disk/{}/write", d.name)),
+                    };
+                    (d, history)
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // first device shown in the disk view
+    pub fn get_disk_selected(&self) -> usize {
+        self.disk_selected
+            .min(self.get_disks().len().saturating_sub(1))
+    }
+
+    pub fn get_interfaces(&self) -> &[Interface] {
+        match self.load.network() {
+            Some(interfaces) => interfaces,
+            None => &[],
+        }
+    }
+
+    // selected row in the interface table
+    pub fn get_net_selected(&self) -> usize {
+        self.net_selected
+            .min(self.get_interfaces().len().saturating_sub(1))
+    }
+
+    // throughput history for the selected interface
+    pub fn get_net_history(&self) -> Option<(&Interface, NetHistory)> {
+        let interface = self.get_interfaces().get(self.get_net_selected())?;
+        let history = NetHistory {
+            rx: self.chart_points(&format!("net/{}/rx", interface.name)),
+            tx: self.chart_points(&format!("net/{}/tx", interface.name)),
+        };
+        Some((interface, history))
+    }
+
+    // Processes after filtering and sorting, with their depth when in tree view
+    pub fn get_processes(&self) -> Vec<(usize, &Process)> {
+        let processes = match self.load.processes() {
+            Some(processes) => processes,
+            None => return Vec::new(),
+        };
+
+        let needle = self.proc_filter.to_lowercase();
+        let mut shown: Vec<&Process> = processes
+            .iter()
+            .filter(|p| {
+                needle.is_empty()
+                    || p.name.to_lowercase().contains(&needle)
+                    || p.command.to_lowercase().contains(&needle)
+                    || p.user.to_lowercase().contains(&needle)
+                    || p.pid.to_string() == needle
+            })
+            .collect();
+
+        shown.sort_by(|a, b| {
+            let order = match self.proc_sort {
+                ProcSort::Pid => a.pid.cmp(&b.pid),
+                ProcSort::User => a.user.cmp(&b.user),
+                ProcSort::Cpu => a.cpu.total_cmp(&b.cpu),
+                ProcSort::Mem => a.rss.cmp(&b.rss),
+                ProcSort::State => a.state.cmp(&b.state),
+                ProcSort::Nice => a.nice.cmp(&b.nice),
+                ProcSort::Command => a.command.cmp(&b.command),
+            };
+            // pid breaks ties so equal rows dont shuffle every tick
+            let order = order.then(a.pid.cmp(&b.pid));
+            if self.proc_sort_desc {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+
+        if self.proc_tree {
+            procs::tree_order(&shown)
+                .into_iter()
+                .map(|(depth, i)| (depth, shown[i]))
+                .collect()
+        } else {
+            shown.into_iter().map(|p| (0, p)).collect()
+        }
+    }
+
+    // selected row in the process table
+    pub fn get_proc_selected(&self) -> usize {
+        let processes = self.get_processes();
+        processes
+            .iter()
+            .position(|(_, p)| Some(p.pid) == self.proc_selected)
+            .unwrap_or(self.proc_row.min(processes.len().saturating_sub(1)))
+    }
+
+    // the process signals go to, None if it has gone since the last sample
+    fn selected_process(&self) -> Option<&Process> {
+        let pid = self.proc_selected?;
+        self.load.processes()?.iter().find(|p| p.pid == pid)
+    }
+
+    fn select_proc(&mut self, row: usize) {
+        let processes = self.get_processes();
+        let row = row.min(processes.len().saturating_sub(1));
+        let pid = processes.get(row).map(|(_, p)| p.pid);
+        self.proc_selected = pid;
+        self.proc_row = row;
+    }
+
+    // keep the selection on the same process after the rows move, or on
+    // whatever took its row if it went away
+    fn follow_proc(&mut self) {
+        self.select_proc(self.get_proc_selected());
+    }
+
+    // message for the status line, dropped after a few seconds
+    pub fn get_status(&self) -> Option<(&str, bool)> {
+        match &self.status {
+            Some((msg, err, at)) if at.elapsed() < STATUS_TIMEOUT => Some((msg, *err)),
+            _ => None,
+        }
+    }
+
+    pub fn set_status(&mut self, msg: String, err: bool) {
+        self.status = Some((msg, err, Instant::now()));
+    }
+
+    // open the confirmation popup for the selected process
+    fn ask_signal(&mut self, key: KeyActions) {
+        if let Some(p) = self.selected_process() {
+            let Some((signal, label)) = signal_for(key, p.state) else {
+                return;
+            };
+            self.popup = Some(ProcAction::Signal {
+                pid: p.pid,
+                name: p.name.clone(),
+                signal,
+                label,
+            });
+        }
+    }
+
+    fn ask_renice(&mut self) {
+        if let Some(p) = self.selected_process() {
+            self.popup = Some(ProcAction::Renice {
+                pid: p.pid,
+                name: p.name.clone(),
+                nice: p.nice,
+            });
+        }
+    }
+
+    // popup keys: confirm, cancel, or nudge the nice value
+    fn handle_popup_key(&mut self, key: KeyActions) {
+        match key {
+            KeyActions::Confirm => {
+                let result = match self.popup.take() {
+                    Some(ProcAction::Signal {
+                        pid,
+                        name,
+                        signal,
+                        label,
+                    }) => procs::send_signal(pid, signal)
+                        .map(|_| format!("Sent {} to {} ({})", label, pid, name))
+                        .map_err(|e| format!("{} to {} ({}) failed: {}", label, pid, name, e)),
+                    Some(ProcAction::Renice { pid, name, nice }) => procs::renice(pid, nice)
+                        .map(|_| format!("Reniced {} ({}) to {}", pid, name, nice))
+                        .map_err(|e| format!("Renice {} ({}) failed: {}", pid, name, e)),
+                    None => return,
+                };
+                match result {
+                    Ok(msg) => self.set_status(msg, false),
+                    Err(msg) => self.set_status(msg, true),
+                }
+            }
+            KeyActions::Up | KeyActions::Down => {
+                if let Some(ProcAction::Renice { nice, .. }) = &mut self.popup {
+                    *nice = step_nice(*nice, key);
                 }
             }
+            KeyActions::Quit => self.state = State::Quit,
+            KeyActions::Cancel | KeyActions::Renice => self.popup = None,
             _ => {}
         }
     }
 
-    // Function to check if key presses were entered
-    pub fn check_keys(&mut self) -> Result<(), ()> {
-        let mut got_message = true;
-
-        match &self.event_handler {
-            None => return Err(()),
-            Some(x) => {
-                while got_message {
-                    let res = x.recv_timeout(Duration::from_millis(250));
-                    match res {
-                        Err(_) => got_message = false,
-                        Ok(msg) => match msg {
-                            Some(key) => match key {
-                                KeyActions::Quit => {
-                                    self.state = State::Quit;
-                                    got_message = false;
-                                }
-                                KeyActions::ToggleUnits => match self.units {
-                                    Units::Celcius => self.units = Units::Fahrenheit,
-                                    Units::Fahrenheit => self.units = Units::Celcius,
-                                },
-                                KeyActions::ClearTemp => {
-                                    self.temp_vec = Vec::new();
-                                }
-                            },
-                            None => {}
-                        },
-                    }
+    fn set_typing(&mut self, typing: bool) {
+        self.typing = typing;
+    }
+
+    // move the selection in whatever list the current view shows
+    fn scroll(&mut self, up: bool) {
+        let step = |selected: usize| {
+            if up {
+                selected.saturating_sub(1)
+            } else {
+                selected + 1
+            }
+        };
+        match self.view {
+            View::Mounts => {
+                self.mount_selected = step(self.get_mount_selected());
+                self.mount_selected = self.get_mount_selected();
+            }
+            View::Disks => {
+                self.disk_selected = step(self.get_disk_selected());
+                self.disk_selected = self.get_disk_selected();
+            }
+            View::Network => {
+                self.net_selected = step(self.get_net_selected());
+                self.net_selected = self.get_net_selected();
+            }
+            View::Processes => {
+                self.select_proc(step(self.get_proc_selected()));
+            }
+            View::Sensors => {
+                self.sensor_selected = step(self.get_sensor_selected());
+                self.sensor_selected = self.get_sensor_selected();
+            }
+            View::Overview | View::Memory | View::Battery => {}
+        }
+    }
+
+    // fold a tick worth of samples in and push the histories
+    pub fn update(&mut self, collected: Vec<Collected>) {
+        // only what was sampled this tick goes in the history, a collector on
+        // a slower schedule or one that failed would repeat its last value
+        let fresh: HashSet<&str> = collected
+            .iter()
+            .filter(|c| c.result.is_ok())
+            .map(|c| c.name)
+            .collect();
+        for c in collected {
+            self.load.apply(c);
+        }
+        let loads = &self.load;
+        let history = &mut self.history;
+        // forget devices that went away
+        if let Some(disks) = loads.disks() {
+            history.retain(|key| match device(key, "disk") {
+                Some(name) => disks.iter().any(|d| d.name == name),
+                None => true,
+            });
+        }
+        if let Some(interfaces) = loads.network() {
+            history.retain(|key| match device(key, "net") {
+                Some(name) => interfaces.iter().any(|i| i.name == name),
+                None => true,
+            });
+        }
+        if fresh.contains("sensors") {
+            let temps = loads.temps();
+            history.retain(|key| match key.strip_prefix("sensor/") {
+                Some(id) => temps.iter().any(|s| s.id == id),
+                None => true,
+            });
+        }
+        let now = history::now();
+        self.updated = now;
+        let mut push = |key: &str, value: f64| history.push(key, now, value);
+        if fresh.contains("sensors") {
+            if let Some(temp) = loads.cpu_temp() {
+                push("temp", temp as f64);
+            }
+            for sensor in loads.temps() {
+                push(&format!("sensor/{}", sensor.id), sensor.celsius as f64);
+            }
+        }
+        if fresh.contains("cpufreq") {
+            if let Some(summary) = cpufreq::summarize(loads.freqs()) {
+                push("freq", summary.avg_mhz);
+            }
+        }
+        if fresh.contains("memory") {
+            if let Some(mem) = &loads.memory() {
+                push("mem", mem.percent(mem.used()));
+                push("swap", mem.swap_percent());
+            }
+        }
+        if fresh.contains(rapl::NAME) {
+            let zones = &loads.rapl();
+            if !zones.is_empty() {
+                push(
+                    "rapl",
+                    zones.iter().filter(|z| z.package).map(|z| z.watts).sum(),
+                );
+            }
+        }
+        if fresh.contains("battery") {
+            if let Some(battery) = loads.power().total() {
+                push("battery", battery.percent as f64);
+                if let Some(watts) = battery.power {
+                    push("battery/power", watts as f64);
                 }
             }
         }
-        Ok(())
+        if fresh.contains("disks") {
+            for disk in loads.disks().unwrap_or_default() {
+                push(&format!("disk/{}/read", disk.name), disk.read_bps);
+                push(&format!("disk/{}/write", disk.name), disk.write_bps);
+            }
+        }
+        if fresh.contains("network") {
+            for interface in loads.network().unwrap_or_default() {
+                push(&format!("net/{}/rx", interface.name), interface.rx_bps);
+                push(&format!("net/{}/tx", interface.name), interface.tx_bps);
+            }
+        }
+        // min and max since start for the sensors view
+        if fresh.contains("sensors") {
+            let values = loads
+                .temps()
+                .iter()
+                .map(|s| (&s.id, s.celsius as f64))
+                .chain(loads.readings().iter().map(|r| (&r.id, r.value)));
+            for (id, value) in values {
+                let seen = self
+                    .sensor_extremes
+                    .entry(id.clone())
+                    .or_insert((value, value));
+                *seen = (seen.0.min(value), seen.1.max(value));
+            }
+        }
+        if fresh.contains("processes") {
+            self.follow_proc();
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyActions) {
+        if self.popup.is_some() {
+            self.handle_popup_key(key);
+            return;
+        }
+        match key {
+            KeyActions::Quit => self.state = State::Quit,
+            KeyActions::SlowerSampling => self.step_interval(true),
+            KeyActions::FasterSampling => self.step_interval(false),
+            KeyActions::ToggleUnits => match self.units {
+                Units::Celcius => self.units = Units::Fahrenheit,
+                Units::Fahrenheit => self.units = Units::Celcius,
+            },
+            KeyActions::ClearTemp => self
+                .history
+                .retain(|key| key != "temp" && key != "freq" && !key.starts_with("sensor/")),
+            KeyActions::WiderWindow => self.step_window(true),
+            KeyActions::NarrowerWindow => self.step_window(false),
+            KeyActions::CycleGraph => self.cycle_graph(),
+            KeyActions::FocusChart => self.focus_next_chart(),
+            KeyActions::CycleFreqChart => {
+                self.freq_chart = match self.freq_chart {
+                    FreqChart::Off => FreqChart::Overlay,
+                    FreqChart::Overlay => FreqChart::Only,
+                    FreqChart::Only => FreqChart::Off,
+                }
+            }
+            KeyActions::SwitchView(view) => self.view = view,
+            KeyActions::Up => self.scroll(true),
+            KeyActions::Down => self.scroll(false),
+            KeyActions::TogglePseudo => self.show_pseudo = !self.show_pseudo,
+            KeyActions::CycleSort => {
+                self.proc_sort = self.proc_sort.next();
+                self.follow_proc();
+            }
+            KeyActions::ReverseSort => {
+                self.proc_sort_desc = !self.proc_sort_desc;
+                self.follow_proc();
+            }
+            KeyActions::ToggleTree => {
+                self.proc_tree = !self.proc_tree;
+                self.follow_proc();
+            }
+            KeyActions::StartFilter => {
+                if let View::Processes = self.view {
+                    self.set_typing(true);
+                }
+            }
+            KeyActions::Input(c) => {
+                self.proc_filter.push(c);
+                self.select_proc(0);
+            }
+            KeyActions::InputBackspace => {
+                self.proc_filter.pop();
+                self.follow_proc();
+            }
+            KeyActions::InputDone => self.set_typing(false),
+            KeyActions::InputCancel => {
+                self.proc_filter.clear();
+                self.set_typing(false);
+                self.follow_proc();
+            }
+            KeyActions::Terminate | KeyActions::Kill | KeyActions::StopContinue
+                if self.view != View::Processes => {}
+            KeyActions::Terminate | KeyActions::Kill | KeyActions::StopContinue => {
+                self.ask_signal(key)
+            }
+            KeyActions::Renice => {
+                if let View::Processes = self.view {
+                    self.ask_renice();
+                }
+            }
+            KeyActions::Confirm | KeyActions::Cancel => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sys_mon::Sample;
+
+    #[test]
+    fn uptime_and_boot_time_for_the_title() {
+        assert_eq!(format_uptime(Duration::from_secs(59)), "0h 0m");
+        assert_eq!(
+            format_uptime(Duration::from_secs(3 * 3600 + 25 * 60)),
+            "3h 25m"
+        );
+        assert_eq!(
+            format_uptime(Duration::from_secs(2 * 86400 + 3600 + 60 + 30)),
+            "2d 1h 1m"
+        );
+        let boot = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        assert_eq!(format_boot_time(boot), "2023-11-14 22:13 UTC");
+    }
+
+    #[test]
+    fn reload_keeps_the_interval_picked_with_the_keys() {
+        let mut app = App::new(Config::default());
+        let schedule = app.schedule_watch();
+        app.handle_key(KeyActions::SlowerSampling);
+        assert_eq!(app.get_interval(), Duration::from_secs(1));
+
+        // an unrelated edit
+        let mut edited = Config {
+            history: 100,
+            ..Config::default()
+        };
+        app.reload_config(Ok(edited.clone()));
+        assert_eq!(app.get_interval(), Duration::from_secs(1));
+
+        // the file's own interval changing does win
+        edited.interval = Duration::from_secs(2);
+        app.reload_config(Ok(edited));
+        assert_eq!(schedule.borrow().interval, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn cycling_one_chart_leaves_the_others_alone() {
+        let mut config = Config::default();
+        config
+            .charts
+            .styles
+            .insert(HistoryChart::Power, GraphType::Bar);
+        let mut app = App::new(config);
+        assert_eq!(app.focused_chart(), Some(HistoryChart::Temp));
+        app.handle_key(KeyActions::CycleGraph);
+        assert!(app.graph(HistoryChart::Temp) == GraphType::SparkLine);
+        assert!(app.graph(HistoryChart::Memory) == GraphType::Braille);
+        assert!(app.graph(HistoryChart::Power) == GraphType::Bar);
+
+        // the memory history panel and power are on the overview too
+        app.handle_key(KeyActions::FocusChart);
+        assert_eq!(app.focused_chart(), Some(HistoryChart::Memory));
+        app.handle_key(KeyActions::FocusChart);
+        app.handle_key(KeyActions::CycleGraph);
+        // power goes on from its own style, bar wraps round to line
+        assert!(app.graph(HistoryChart::Power) == GraphType::Line);
+        assert!(app.graph(HistoryChart::Temp) == GraphType::SparkLine);
+        let saved: Vec<_> = app.take_graph().into_iter().map(|(c, _)| c).collect();
+        assert_eq!(saved, [HistoryChart::Temp, HistoryChart::Power]);
+    }
+
+    #[test]
+    fn a_sensor_that_goes_away_takes_its_history_with_it() {
+        let sensor = |id: &str| TempSensor {
+            id: id.to_owned(),
+            chip: "coretemp".to_owned(),
+            label: id.to_owned(),
+            celsius: 40.0,
+            max: None,
+            crit: None,
+        };
+        let sensors = |ids: &[&str]| Collected {
+            name: "sensors",
+            result: Ok(Sample::Sensors {
+                temps: ids.iter().map(|id| sensor(id)).collect(),
+                readings: Vec::new(),
+                cpu_temp: None,
+            }),
+        };
+        let mut app = App::new(Config::default());
+        app.update(vec![sensors(&["hwmon1/temp1", "hwmon1/temp2"])]);
+        assert_eq!(app.history.points("sensor/hwmon1/temp2").len(), 1);
+
+        app.update(vec![sensors(&["hwmon1/temp1"])]);
+        assert_eq!(app.history.points("sensor/hwmon1/temp1").len(), 2);
+        assert!(app.history.points("sensor/hwmon1/temp2").is_empty());
+    }
+
+    #[test]
+    fn process_keys_pick_the_signal() {
+        assert_eq!(
+            signal_for(KeyActions::Terminate, 'S'),
+            Some((libc::SIGTERM, "SIGTERM"))
+        );
+        assert_eq!(
+            signal_for(KeyActions::Kill, 'R'),
+            Some((libc::SIGKILL, "SIGKILL"))
+        );
+        assert_eq!(
+            signal_for(KeyActions::StopContinue, 'R'),
+            Some((libc::SIGSTOP, "SIGSTOP"))
+        );
+        // a stopped process gets continued instead
+        assert_eq!(
+            signal_for(KeyActions::StopContinue, 'T'),
+            Some((libc::SIGCONT, "SIGCONT"))
+        );
+        assert_eq!(signal_for(KeyActions::Renice, 'S'), None);
+    }
+
+    #[test]
+    fn nice_steps_and_stops_at_the_ends() {
+        assert_eq!(step_nice(0, KeyActions::Up), -1);
+        assert_eq!(step_nice(0, KeyActions::Down), 1);
+        assert_eq!(step_nice(-20, KeyActions::Up), -20);
+        assert_eq!(step_nice(19, KeyActions::Down), 19);
+        assert_eq!(step_nice(-19, KeyActions::Up), -20);
+        assert_eq!(step_nice(18, KeyActions::Down), 19);
+    }
+
+    #[test]
+    fn selection_follows_the_pid() {
+        let process = |pid: u32, cpu: f32| Process {
+            pid,
+            ppid: 1,
+            user: "root".to_owned(),
+            cpu,
+            rss: bytesize::ByteSize::kib(pid as u64),
+            state: 'S',
+            nice: 0,
+            name: format!("proc{}", pid),
+            command: format!("/bin/proc{}", pid),
+        };
+        let sample = |procs: Vec<Process>| {
+            vec![Collected {
+                name: "processes",
+                result: Ok(Sample::Processes(procs)),
+            }]
+        };
+        let selected = |app: &App| app.selected_process().map(|p| p.pid);
+        let mut app = App::new(Config::default());
+        app.view = View::Processes;
+
+        // busiest first: 2, 3, 1
+        app.update(sample(vec![
+            process(1, 10.0),
+            process(2, 50.0),
+            process(3, 30.0),
+        ]));
+        app.handle_key(KeyActions::Down);
+        app.handle_key(KeyActions::Down);
+        assert_eq!(selected(&app), Some(1));
+        assert_eq!(app.get_proc_selected(), 2);
+
+        // re-sorting moves the row, not the selection
+        app.handle_key(KeyActions::ReverseSort);
+        assert_eq!(selected(&app), Some(1));
+        assert_eq!(app.get_proc_selected(), 0);
+        app.handle_key(KeyActions::ReverseSort);
+
+        // so does a new sample that reorders things
+        app.update(sample(vec![
+            process(1, 90.0),
+            process(2, 50.0),
+            process(3, 30.0),
+        ]));
+        assert_eq!(selected(&app), Some(1));
+        assert_eq!(app.get_proc_selected(), 0);
+
+        // once it exits whatever now sits in its row is selected
+        app.update(sample(vec![process(2, 50.0), process(3, 30.0)]));
+        assert_eq!(app.get_proc_selected(), 0);
+        assert_eq!(selected(&app), Some(2));
+    }
+
+    #[test]
+    fn chart_window_bounds_and_stepping() {
+        let mut app = App::new(Config::default());
+        app.updated = 10_000.0;
+        assert!(app.window == Window::FiveMinutes);
+        assert_eq!(app.get_chart_window(), [9_700.0, 10_000.0]);
+
+        // narrowest is a minute, further presses stay there
+        app.handle_key(KeyActions::NarrowerWindow);
+        app.handle_key(KeyActions::NarrowerWindow);
+        assert!(app.window == Window::Minute);
+        assert_eq!(app.get_chart_window(), [9_940.0, 10_000.0]);
+
+        for _ in 0..WINDOWS.len() {
+            app.handle_key(KeyActions::WiderWindow);
+        }
+        assert!(app.window == Window::All);
+
+        // all reaches back to the oldest sample, with nothing yet it still
+        // has some width to draw
+        assert_eq!(app.get_chart_window(), [10_000.0, 10_001.0]);
+        app.history.push("mem", 8_200.0, 40.0);
+        app.history.push("mem", 9_500.0, 45.0);
+        assert_eq!(app.get_chart_window(), [8_200.0, 10_000.0]);
+        assert_eq!(app.get_mem_points().len(), 2);
+
+        app.handle_key(KeyActions::NarrowerWindow);
+        assert!(app.window == Window::Hour);
+        assert_eq!(app.get_chart_window(), [6_400.0, 10_000.0]);
+    }
+
+    #[test]
+    fn interval_keys_stay_in_range() {
+        let mut app = App::new(Config {
+            interval: Duration::from_secs(10),
+            ..Config::default()
+        });
+        let mut schedule = app.schedule_watch();
+        app.handle_key(KeyActions::SlowerSampling);
+        assert!(schedule.has_changed().unwrap());
+        assert_eq!(
+            schedule.borrow_and_update().interval,
+            Duration::from_secs(30)
+        );
+        app.handle_key(KeyActions::SlowerSampling);
+        assert_eq!(app.get_interval(), Duration::from_secs(30));
+
+        for _ in 0..INTERVALS.len() + 1 {
+            app.handle_key(KeyActions::FasterSampling);
+        }
+        assert_eq!(schedule.borrow().interval, Duration::from_millis(100));
     }
 }
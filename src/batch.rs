@@ -0,0 +1,181 @@
+/*
+- --once, --count, --json and --csv. Samples the same way the monitor does but
+  prints to stdout instead of drawing, for scripts and cron jobs
+*/
+use crate::app::Units;
+use crate::cli::Format;
+use crate::config::Config;
+use std::error::Error;
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use sys_mon::{snapshot_with, Loads, Registry};
+use systemstat::{Platform, System};
+
+// the headline numbers text and csv print, json gets everything
+fn columns(loads: &Loads, units: Units) -> Vec<(&'static str, Option<f64>)> {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let load_avg = loads.load_average();
+    let mem = loads.memory();
+    // loopback traffic never leaves the machine
+    let interfaces = || {
+        loads
+            .network()
+            .into_iter()
+            .flatten()
+            .filter(|i| i.name != "lo")
+    };
+    let disks = || loads.disks().into_iter().flatten();
+    let rapl = loads.rapl();
+    vec![
+        ("time", Some(since_epoch.as_secs() as f64)),
+        ("cpu", loads.cpu().map(|c| c.busy() as f64)),
+        ("temp", loads.cpu_temp().map(|t| units.convert(t) as f64)),
+        ("mem", mem.map(|m| m.percent(m.used()))),
+        ("swap", mem.map(|m| m.swap_percent())),
+        ("load1", load_avg.map(|l| l.0 as f64)),
+        ("load5", load_avg.map(|l| l.1 as f64)),
+        ("load15", load_avg.map(|l| l.2 as f64)),
+        (
+            "net_rx",
+            loads
+                .network()
+                .map(|_| interfaces().map(|i| i.rx_bps).sum()),
+        ),
+        (
+            "net_tx",
+            loads
+                .network()
+                .map(|_| interfaces().map(|i| i.tx_bps).sum()),
+        ),
+        (
+            "disk_read",
+            loads.disks().map(|_| disks().map(|d| d.read_bps).sum()),
+        ),
+        (
+            "disk_write",
+            loads.disks().map(|_| disks().map(|d| d.write_bps).sum()),
+        ),
+        ("battery", loads.power().total().map(|b| b.percent as f64)),
+        (
+            "power",
+            (!rapl.is_empty()).then(|| rapl.iter().filter(|z| z.package).map(|z| z.watts).sum()),
+        ),
+    ]
+}
+
+// "cpu 3.1%  temp 45.0C ..." with the rates in bytes per second
+fn text_line(columns: &[(&str, Option<f64>)], units: Units) -> String {
+    columns
+        .iter()
+        .filter(|(name, _)| *name != "time")
+        .map(|(name, value)| {
+            let value = match (name, value) {
+                (_, None) => "NA".to_owned(),
+                (&"cpu" | &"mem" | &"swap" | &"battery", Some(v)) => format!("{:.1}%", v),
+                (&"temp", Some(v)) => format!("{:.1}{}", v, units.symbol()),
+                (&"power", Some(v)) => format!("{:.1}W", v),
+                (name, Some(v)) if name.starts_with("load") => format!("{:.2}", v),
+                (_, Some(v)) => format!("{}/s", bytesize::ByteSize(*v as u64)),
+            };
+            format!("{} {}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+// numbers as they are and empty cells for what we dont know
+fn csv_line(columns: &[(&str, Option<f64>)]) -> String {
+    columns
+        .iter()
+        .map(|(name, value)| match (name, value) {
+            (_, None) => String::new(),
+            (&"time", Some(v)) => format!("{:.0}", v),
+            (_, Some(v)) => format!("{:.2}", v),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(feature = "serde")]
+fn json_line(loads: &Loads) -> Result<String, Box<dyn Error>> {
+    Ok(serde_json::to_string(loads)?)
+}
+
+#[cfg(not(feature = "serde"))]
+fn json_line(_loads: &Loads) -> Result<String, Box<dyn Error>> {
+    Err("--json needs sys_mon built with the serde feature".into())
+}
+
+// Print count samples, one interval apart. A count of 0 keeps going until killed
+pub async fn run(count: u64, format: Format, config: &Config) -> Result<(), Box<dyn Error>> {
+    let (interval, units) = (config.interval, config.units);
+    let mut collectors = Registry::with_defaults();
+    collectors.set_intervals(&config.collectors);
+    // the first sample is a snapshot over one interval, it blocks for that long
+    let (mut collectors, sys, mut loads) = tokio::task::spawn_blocking(move || {
+        let sys = System::new();
+        let loads = snapshot_with(&mut collectors, &sys, interval);
+        (collectors, sys, loads)
+    })
+    .await?;
+    let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+
+    let mut taken = 0;
+    while count == 0 || taken < count {
+        if taken > 0 {
+            ticks.tick().await;
+            for collected in collectors.collect_due(&sys) {
+                loads.apply(collected);
+            }
+        }
+        let columns = columns(&loads, units);
+        let mut lines = Vec::new();
+        match format {
+            Format::Text => lines.push(text_line(&columns, units)),
+            Format::Csv => {
+                if taken == 0 {
+                    let names: Vec<&str> = columns.iter().map(|(name, _)| *name).collect();
+                    lines.push(names.join(","));
+                }
+                lines.push(csv_line(&columns));
+            }
+            Format::Json => lines.push(json_line(&loads)?),
+        }
+
+        let mut stdout = io::stdout().lock();
+        let written = lines
+            .iter()
+            .try_for_each(|line| writeln!(stdout, "{}", line))
+            .and_then(|_| stdout.flush());
+        match written {
+            // the reader went away, piped into head or similar
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => break,
+            res => res?,
+        }
+        taken += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_values_stay_visible() {
+        let columns = [
+            ("time", Some(1700000000.0)),
+            ("cpu", Some(12.345)),
+            ("temp", None),
+            ("load1", Some(0.5)),
+            ("net_rx", Some(2048.0)),
+        ];
+        assert_eq!(
+            text_line(&columns, Units::Celcius),
+            "cpu 12.3%  temp NA  load1 0.50  net_rx 2.0 KB/s"
+        );
+        assert_eq!(csv_line(&columns), "1700000000,12.35,,0.50,2048.00");
+    }
+}
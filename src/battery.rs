@@ -0,0 +1,290 @@
+/*
+- Batteries and AC adapters from /sys/class/power_supply. Drivers report either
+  energy in uWh or charge in uAh, charge gets turned into energy with the voltage
+*/
+use crate::collector::{Collector, Sample};
+use crate::sysfs::{self, read_number, read_text};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use systemstat::System;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub enum Status {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+impl Status {
+    fn parse(text: &str) -> Self {
+        match text {
+            "Charging" => Status::Charging,
+            "Discharging" => Status::Discharging,
+            "Full" => Status::Full,
+            "Not charging" => Status::NotCharging,
+            _ => Status::Unknown,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Status::Charging => "charging",
+            Status::Discharging => "discharging",
+            Status::Full => "full",
+            Status::NotCharging => "not charging",
+            Status::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct Battery {
+    pub name: String,
+    pub status: Status,
+    pub percent: f32,
+    // watt hours
+    pub energy_now: Option<f32>,
+    pub energy_full: Option<f32>,
+    pub energy_design: Option<f32>,
+    // watts, always positive, status says which way it goes
+    pub power: Option<f32>,
+    pub cycles: Option<u32>,
+}
+
+impl Battery {
+    // full capacity as a percent of what it shipped with
+    pub fn health(&self) -> Option<f32> {
+        match (self.energy_full, self.energy_design) {
+            (Some(full), Some(design)) if design > 0.0 => Some(full / design * 100.0),
+            _ => None,
+        }
+    }
+
+    // time to empty while discharging, time to full while charging
+    pub fn time_left(&self) -> Option<Duration> {
+        time_left(
+            self.status,
+            self.energy_now?,
+            self.energy_full?,
+            self.power?,
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct PowerSupplies {
+    pub batteries: Vec<Battery>,
+    // None when there is no mains adapter to ask
+    pub ac_power: Option<bool>,
+}
+
+impl PowerSupplies {
+    // every battery lumped together, weighted by capacity when we know it
+    pub fn total(&self) -> Option<Battery> {
+        let first = self.batteries.first()?;
+        if self.batteries.len() == 1 {
+            return Some(first.clone());
+        }
+        let sum =
+            |f: fn(&Battery) -> Option<f32>| -> Option<f32> { self.batteries.iter().map(f).sum() };
+        let energy_now = sum(|b| b.energy_now);
+        let energy_full = sum(|b| b.energy_full);
+        let percent = match (energy_now, energy_full) {
+            (Some(now), Some(full)) if full > 0.0 => now / full * 100.0,
+            _ => {
+                self.batteries.iter().map(|b| b.percent).sum::<f32>() / self.batteries.len() as f32
+            }
+        };
+        // one battery charging while the other drains is still charging overall
+        let status = if self.batteries.iter().any(|b| b.status == Status::Charging) {
+            Status::Charging
+        } else if self
+            .batteries
+            .iter()
+            .any(|b| b.status == Status::Discharging)
+        {
+            Status::Discharging
+        } else {
+            first.status
+        };
+        Some(Battery {
+            name: "total".to_owned(),
+            status,
+            percent,
+            energy_now,
+            energy_full,
+            energy_design: sum(|b| b.energy_design),
+            power: sum(|b| b.power),
+            cycles: None,
+        })
+    }
+}
+
+fn time_left(status: Status, now: f32, full: f32, power: f32) -> Option<Duration> {
+    if power <= 0.0 {
+        return None;
+    }
+    let hours = match status {
+        Status::Discharging => now / power,
+        Status::Charging => (full - now).max(0.0) / power,
+        _ => return None,
+    };
+    Some(Duration::from_secs_f32(hours * 3600.0))
+}
+
+// "3h 07m" style, minutes are within the hour
+pub fn format_duration(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+}
+
+// every battery plus the mains adapter
+pub struct BatteryCollector;
+
+impl Collector for BatteryCollector {
+    fn name(&self) -> &'static str {
+        "battery"
+    }
+
+    // no batteries isnt an error, plenty of machines dont have one
+    fn collect(&mut self, _sys: &System) -> io::Result<Sample> {
+        Ok(Sample::Power(read_power_supplies()))
+    }
+}
+
+pub fn read_power_supplies() -> PowerSupplies {
+    read_power_supplies_at(Path::new("/sys/class/power_supply"))
+}
+
+pub fn read_power_supplies_at(root: &Path) -> PowerSupplies {
+    let mut supplies = PowerSupplies::default();
+    for (_, dir) in sysfs::dirs(root) {
+        let kind = read_text(&dir.join("type")).unwrap_or_default();
+        match kind.as_str() {
+            "Mains" => {
+                let online = read_number(&dir.join("online")) == Some(1.0);
+                supplies.ac_power = Some(supplies.ac_power.unwrap_or(false) || online);
+            }
+            // mice and keyboards show up here with scope Device, skip them
+            "Battery" if read_text(&dir.join("scope")).as_deref() != Some("Device") => {
+                if let Some(battery) = read_battery(&dir) {
+                    supplies.batteries.push(battery);
+                }
+            }
+            _ => {}
+        }
+    }
+    supplies
+}
+
+fn read_battery(dir: &Path) -> Option<Battery> {
+    if read_number(&dir.join("present")) == Some(0.0) {
+        return None;
+    }
+    let name = dir.file_name()?.to_string_lossy().into_owned();
+    let micro = |file: &str| read_number(&dir.join(file)).map(|v: f64| v / 1_000_000.0);
+    // charge in Ah times volts is Wh, design voltage is steadier than the live one
+    let volts = micro("voltage_min_design").or_else(|| micro("voltage_now"));
+    let energy = |kind: &str| {
+        micro(&format!("energy_{}", kind))
+            .or_else(|| Some(micro(&format!("charge_{}", kind))? * volts?))
+            .map(|v| v as f32)
+    };
+    let energy_now = energy("now");
+    let energy_full = energy("full");
+    let power = micro("power_now")
+        .or_else(|| Some(micro("current_now")? * micro("voltage_now")?))
+        .map(|v| v.abs() as f32);
+    let percent = match read_number::<f32>(&dir.join("capacity")) {
+        Some(p) => p,
+        None => match (energy_now, energy_full) {
+            (Some(now), Some(full)) if full > 0.0 => now / full * 100.0,
+            _ => return None,
+        },
+    };
+    Some(Battery {
+        name,
+        status: Status::parse(&read_text(&dir.join("status")).unwrap_or_default()),
+        percent: percent.min(100.0),
+        energy_now,
+        energy_full,
+        energy_design: energy("full_design"),
+        power,
+        // some drivers report 0 when they dont track it
+        cycles: read_number(&dir.join("cycle_count")).filter(|c| *c > 0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn reads_charge_based_battery() {
+        let root = TestDir::with_files(
+            "battery",
+            &[
+                ("BAT0/type", "Battery"),
+                ("BAT0/status", "Discharging"),
+                ("BAT0/capacity", "50"),
+                ("BAT0/charge_now", "2000000"),
+                ("BAT0/charge_full", "4000000"),
+                ("BAT0/charge_full_design", "5000000"),
+                ("BAT0/current_now", "1000000"),
+                ("BAT0/voltage_now", "10000000"),
+                ("BAT0/voltage_min_design", "10000000"),
+                ("BAT0/cycle_count", "321"),
+                ("AC/type", "Mains"),
+                ("AC/online", "0"),
+                ("hidpp_battery_0/type", "Battery"),
+                ("hidpp_battery_0/scope", "Device"),
+                ("hidpp_battery_0/capacity", "90"),
+            ],
+        );
+
+        let supplies = read_power_supplies_at(&root);
+
+        assert_eq!(supplies.ac_power, Some(false));
+        assert_eq!(supplies.batteries.len(), 1);
+        let bat = &supplies.batteries[0];
+        assert_eq!(bat.status, Status::Discharging);
+        assert_eq!(bat.energy_now, Some(20.0));
+        assert_eq!(bat.power, Some(10.0));
+        assert_eq!(bat.health(), Some(80.0));
+        assert_eq!(bat.cycles, Some(321));
+        // 20 Wh at 10 W
+        assert_eq!(bat.time_left(), Some(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn formats_minutes_within_the_hour() {
+        assert_eq!(
+            format_duration(Duration::from_secs(3 * 3600 + 7 * 60 + 59)),
+            "3h 07m"
+        );
+        assert_eq!(
+            time_left(Status::Charging, 30.0, 40.0, 20.0),
+            Some(Duration::from_secs(1800))
+        );
+        assert_eq!(time_left(Status::Full, 40.0, 40.0, 0.0), None);
+    }
+}
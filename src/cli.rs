@@ -0,0 +1,98 @@
+/*
+- Command line options. Anything not given here comes from the config file,
+  or the defaults when that doesnt say either
+*/
+use crate::app::{Panel, Units};
+use crate::config::parse_interval;
+use clap::{ArgGroup, Parser};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(version, about = "A terminal system monitor for Linux")]
+#[command(group(ArgGroup::new("format").args(["json", "csv"])))]
+pub struct Args {
+    /// Time between samples from 100ms to 30s, like 250ms, 2s or plain milliseconds [default: 500ms]
+    #[arg(short, long, value_name = "TIME", value_parser = parse_interval)]
+    pub interval: Option<Duration>,
+
+    /// Temperature unit to start in, TAB still switches [default: c]
+    #[arg(short, long, value_enum)]
+    pub unit: Option<Units>,
+
+    /// Overview panels to show, comma separated [default: all of them]
+    #[arg(short, long, value_enum, value_delimiter = ',')]
+    pub panels: Option<Vec<Panel>>,
+
+    /// Points each chart keeps at full detail, and again at 10x and 100x coarser;
+    /// 300 reaches back over 4 hours at 500ms samples [default: 300]
+    #[arg(long, value_name = "POINTS", value_parser = clap::value_parser!(u64).range(2..))]
+    pub history: Option<u64>,
+
+    /// Take one sample, print it and exit
+    #[arg(long, conflicts_with = "count")]
+    pub once: bool,
+
+    /// Take N samples one interval apart, print them and exit. 0 runs until killed
+    #[arg(short = 'n', long, value_name = "N")]
+    pub count: Option<u64>,
+
+    /// Print samples as JSON lines instead of starting the monitor
+    #[arg(long)]
+    pub json: bool,
+
+    /// Print samples as CSV instead of starting the monitor
+    #[arg(long)]
+    pub csv: bool,
+
+    /// Config file to read instead of $XDG_CONFIG_HOME/sys-mon/config.toml
+    #[arg(short, long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+}
+
+// how batch mode prints each sample
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Csv,
+    Json,
+}
+
+impl Args {
+    // Some(samples, format) when we should print instead of drawing.
+    // A format on its own means one sample
+    pub fn batch(&self) -> Option<(u64, Format)> {
+        let format = if self.json {
+            Format::Json
+        } else if self.csv {
+            Format::Csv
+        } else {
+            Format::Text
+        };
+        match self.count {
+            Some(count) => Some((count, format)),
+            None if self.once || format != Format::Text => Some((1, format)),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_batch_modes() {
+        let batch = |args: &[&str]| {
+            Args::try_parse_from(["sys_mon"].iter().chain(args))
+                .unwrap()
+                .batch()
+        };
+        assert!(batch(&[]).is_none());
+        assert!(batch(&["--json"]) == Some((1, Format::Json)));
+        assert!(batch(&["--count", "3", "--csv"]) == Some((3, Format::Csv)));
+        assert!(batch(&["--once"]) == Some((1, Format::Text)));
+        assert!(Args::try_parse_from(["sys_mon", "--json", "--csv"]).is_err());
+        assert!(Args::try_parse_from(["sys_mon", "--panels", "load,nope"]).is_err());
+    }
+}
@@ -0,0 +1,348 @@
+/*
+- Metric sources. Each one is a Collector that turns into a Sample, the poller
+  runs whatever is due in the registry every tick and the app folds the samples
+  into its Loads. A new source only needs a Collector impl and a Sample variant
+*/
+use crate::battery::PowerSupplies;
+use crate::cpufreq::CoreFreq;
+use crate::disks::DiskIo;
+use crate::loads::CoreLoad;
+use crate::memory::MemInfo;
+use crate::mounts::MountInfo;
+use crate::network::Interface;
+use crate::procs::Process;
+use crate::psi::Psi;
+use crate::rapl::PowerZone;
+use crate::sensors::{Reading, TempSensor};
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::io;
+use std::time::{Duration, Instant};
+use systemstat::{CPULoad, DelayedMeasurement, Platform, System};
+use time::OffsetDateTime;
+
+/// What one collector found this tick
+pub enum Sample {
+    Cpu {
+        total: CoreLoad,
+        cores: Vec<CoreLoad>,
+    },
+    Sensors {
+        temps: Vec<TempSensor>,
+        readings: Vec<Reading>,
+        /// best guess at the package temperature
+        cpu_temp: Option<f32>,
+    },
+    Freqs(Vec<CoreFreq>),
+    Psi(Psi),
+    Power(PowerSupplies),
+    Rapl(Vec<PowerZone>),
+    Host {
+        load_avg: Option<(f32, f32, f32)>,
+        uptime: Option<Duration>,
+        boot_time: Option<OffsetDateTime>,
+        host: Option<(String, String)>,
+    },
+    Mounts(Vec<MountInfo>),
+    Disks(Vec<DiskIo>),
+    Network(Vec<Interface>),
+    Processes(Vec<Process>),
+    Memory(MemInfo),
+}
+
+/// A metric source the [`Registry`] runs on its own schedule
+pub trait Collector: Send {
+    /// Short and unique, shown when the collector fails
+    fn name(&self) -> &'static str;
+
+    /// How often this one wants to run. None keeps up with the sample interval,
+    /// and nothing runs more often than that
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Read the source once
+    fn collect(&mut self, sys: &System) -> io::Result<Sample>;
+}
+
+/// One collector's result, errors go to the app so it can say what is missing
+pub struct Collected {
+    pub name: &'static str,
+    pub result: io::Result<Sample>,
+}
+
+struct Entry {
+    collector: Box<dyn Collector>,
+    last: Option<Instant>,
+    // set by the user, wins over what the collector asks for
+    interval: Option<Duration>,
+}
+
+/// The collectors to run and when each last ran
+#[derive(Default)]
+pub struct Registry {
+    entries: Vec<Entry>,
+}
+
+impl Registry {
+    /// Every source the monitor knows about
+    pub fn with_defaults() -> Self {
+        let mut registry = Registry::default();
+        registry.register(CpuCollector::new());
+        registry.register(crate::sensors::SensorCollector);
+        registry.register(crate::cpufreq::FreqCollector);
+        registry.register(crate::psi::PsiCollector);
+        registry.register(crate::battery::BatteryCollector);
+        registry.register(crate::rapl::RaplSampler::default());
+        registry.register(HostCollector);
+        registry.register(crate::mounts::MountCollector);
+        registry.register(crate::disks::DiskSampler::default());
+        registry.register(crate::network::NetSampler::default());
+        registry.register(crate::procs::ProcSampler::default());
+        registry.register(crate::memory::MemoryCollector);
+        registry
+    }
+
+    /// Add a collector, it runs on the next [`Registry::collect_due`]
+    pub fn register(&mut self, collector: impl Collector + 'static) {
+        self.entries.push(Entry {
+            collector: Box::new(collector),
+            last: None,
+            interval: None,
+        });
+    }
+
+    /// Collector names in the order they run
+    pub fn names(&self) -> Vec<&'static str> {
+        self.entries.iter().map(|e| e.collector.name()).collect()
+    }
+
+    /// How often each named collector runs, anything left out goes back to its own interval
+    pub fn set_intervals(&mut self, intervals: &BTreeMap<String, Duration>) {
+        for entry in &mut self.entries {
+            entry.interval = intervals.get(entry.collector.name()).copied();
+        }
+    }
+
+    /// Run everything whose own interval is up. The first call runs them all
+    pub fn collect_due(&mut self, sys: &System) -> Vec<Collected> {
+        let now = Instant::now();
+        let mut collected = Vec::new();
+        for entry in &mut self.entries {
+            let due = match (entry.last, entry.interval.or(entry.collector.interval())) {
+                (Some(last), Some(interval)) => now.duration_since(last) >= interval,
+                _ => true,
+            };
+            if !due {
+                continue;
+            }
+            entry.last = Some(now);
+            collected.push(Collected {
+                name: entry.collector.name(),
+                result: entry.collector.collect(sys),
+            });
+        }
+        collected
+    }
+
+    /// Run every collector whatever its interval, for a one off sample that
+    /// has to have all of them
+    pub fn collect_all(&mut self, sys: &System) -> Vec<Collected> {
+        for entry in &mut self.entries {
+            entry.last = None;
+        }
+        self.collect_due(sys)
+    }
+}
+
+/// Total and per core load between this collect and the last one
+pub struct CpuCollector {
+    total: Option<DelayedMeasurement<CPULoad>>,
+    cores: Option<DelayedMeasurement<Vec<CPULoad>>>,
+}
+
+impl CpuCollector {
+    /// Starts measuring straight away so the first collect has a window to report
+    pub fn new() -> Self {
+        let sys = System::new();
+        CpuCollector {
+            total: sys.cpu_load_aggregate().ok(),
+            cores: sys.cpu_load().ok(),
+        }
+    }
+}
+
+impl Default for CpuCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Collector for CpuCollector {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn collect(&mut self, sys: &System) -> io::Result<Sample> {
+        let total = self.total.take();
+        let cores = self.cores.take();
+        // start both measurements together so they cover the same window
+        self.total = sys.cpu_load_aggregate().ok();
+        self.cores = sys.cpu_load().ok();
+
+        let total = total
+            .ok_or_else(|| io::Error::other("could not read /proc/stat"))?
+            .done()?;
+        // per core is left empty if we error
+        let cores = match cores.map(|c| c.done()) {
+            Some(Ok(cores)) => cores.iter().map(CoreLoad::from).collect(),
+            _ => Vec::new(),
+        };
+        Ok(Sample::Cpu {
+            total: CoreLoad::from(&total),
+            cores,
+        })
+    }
+}
+
+/// Load average, uptime and boot time for the title bar
+pub struct HostCollector;
+
+impl Collector for HostCollector {
+    fn name(&self) -> &'static str {
+        "host"
+    }
+
+    fn collect(&mut self, sys: &System) -> io::Result<Sample> {
+        Ok(Sample::Host {
+            load_avg: sys.load_average().ok().map(|l| (l.one, l.five, l.fifteen)),
+            uptime: sys.uptime().ok(),
+            boot_time: sys.boot_time().ok(),
+            host: uname(),
+        })
+    }
+}
+
+// hostname and kernel release straight from uname(2)
+fn uname() -> Option<(String, String)> {
+    let mut info: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut info) } != 0 {
+        return None;
+    }
+    Some(host_names(&info))
+}
+
+// both fields are nul terminated by the kernel
+fn host_names(info: &libc::utsname) -> (String, String) {
+    let field = |raw: &[libc::c_char]| {
+        unsafe { CStr::from_ptr(raw.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    (field(&info.nodename), field(&info.release))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // counts its runs so the tests can see what the registry thought was due
+    struct Fake {
+        name: &'static str,
+        interval: Option<Duration>,
+        runs: Arc<AtomicUsize>,
+    }
+
+    impl Collector for Fake {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn interval(&self) -> Option<Duration> {
+            self.interval
+        }
+
+        fn collect(&mut self, _sys: &System) -> io::Result<Sample> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(Sample::Processes(Vec::new()))
+        }
+    }
+
+    fn fake(
+        registry: &mut Registry,
+        name: &'static str,
+        interval: Option<Duration>,
+    ) -> Arc<AtomicUsize> {
+        let runs = Arc::new(AtomicUsize::new(0));
+        registry.register(Fake {
+            name,
+            interval,
+            runs: runs.clone(),
+        });
+        runs
+    }
+
+    fn ran(collected: Vec<Collected>) -> Vec<&'static str> {
+        collected.iter().map(|c| c.name).collect()
+    }
+
+    #[test]
+    fn first_collect_runs_everything() {
+        let mut registry = Registry::default();
+        fake(&mut registry, "fast", None);
+        fake(&mut registry, "slow", Some(Duration::from_secs(3600)));
+        assert_eq!(ran(registry.collect_due(&System::new())), ["fast", "slow"]);
+    }
+
+    #[test]
+    fn skips_a_collector_whose_interval_is_not_up() {
+        let sys = System::new();
+        let mut registry = Registry::default();
+        let fast = fake(&mut registry, "fast", None);
+        let slow = fake(&mut registry, "slow", Some(Duration::from_secs(3600)));
+        registry.collect_due(&sys);
+        assert_eq!(ran(registry.collect_due(&sys)), ["fast"]);
+        assert_eq!(fast.load(Ordering::SeqCst), 2);
+        assert_eq!(slow.load(Ordering::SeqCst), 1);
+
+        // a one off snapshot still gets it
+        assert_eq!(ran(registry.collect_all(&sys)), ["fast", "slow"]);
+    }
+
+    #[test]
+    fn user_interval_beats_the_collectors_own() {
+        let sys = System::new();
+        let mut registry = Registry::default();
+        fake(&mut registry, "wants-slow", Some(Duration::from_secs(3600)));
+        fake(&mut registry, "wants-fast", None);
+        registry.set_intervals(&BTreeMap::from([
+            ("wants-slow".to_owned(), Duration::ZERO),
+            ("wants-fast".to_owned(), Duration::from_secs(3600)),
+        ]));
+        registry.collect_due(&sys);
+        assert_eq!(ran(registry.collect_due(&sys)), ["wants-slow"]);
+
+        // leaving it out goes back to what the collector asks for
+        registry.set_intervals(&BTreeMap::new());
+        assert_eq!(ran(registry.collect_due(&sys)), ["wants-fast"]);
+    }
+
+    #[test]
+    fn hostname_and_release_from_utsname() {
+        let mut info: libc::utsname = unsafe { std::mem::zeroed() };
+        let fill = |field: &mut [libc::c_char], text: &str| {
+            for (to, from) in field.iter_mut().zip(text.bytes()) {
+                *to = from as libc::c_char;
+            }
+        };
+        fill(&mut info.nodename, "buildbox");
+        fill(&mut info.release, "6.8.0-45-generic");
+        fill(&mut info.sysname, "Linux");
+        assert_eq!(
+            host_names(&info),
+            ("buildbox".to_owned(), "6.8.0-45-generic".to_owned())
+        );
+    }
+}
@@ -0,0 +1,647 @@
+/*
+- The config file, $XDG_CONFIG_HOME/sys-mon/config.toml unless --config says
+  otherwise. Every setting is optional and command line flags win over it.
+  The app polls the file and applies edits while running. assets/config.toml
+  shows every setting
+*/
+use crate::app::{format_interval, GraphType, HistoryChart, Panel, Units, INTERVALS};
+use crate::cli::Args;
+use crate::events::{default_keys, parse_key, Keymap};
+use clap::ValueEnum;
+use crossterm::event::KeyCode;
+use ratatui::style::Color;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use sys_mon::{Registry, Schedule};
+use the_serde::Deserialize;
+
+// where a reading turns yellow and then red
+#[derive(Clone, Copy, Deserialize)]
+#[serde(crate = "the_serde", deny_unknown_fields)]
+pub struct Levels {
+    pub warn: f64,
+    pub crit: f64,
+}
+
+#[derive(Clone, Copy)]
+pub struct Thresholds {
+    // cpu, memory, swap and disk busy percent
+    pub usage: Levels,
+    // PSI averages
+    pub psi: Levels,
+    // battery percent, this one counts down so crit is the lower number
+    pub battery: Levels,
+}
+
+// how the history charts are drawn
+#[derive(Clone)]
+pub struct Charts {
+    // hwmon max and crit as lines across the temperature chart
+    pub limits: bool,
+    // braille for any chart not in here, sparklines for disks and power
+    pub styles: HashMap<HistoryChart, GraphType>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub ok: Color,
+    pub warn: Color,
+    pub crit: Color,
+    pub footer: Color,
+}
+
+// Everything the app and poller can be told, already checked
+#[derive(Clone)]
+pub struct Config {
+    pub interval: Duration,
+    pub render_rate: Duration,
+    // points kept by each rolling chart
+    pub history: usize,
+    pub units: Units,
+    pub panels: Vec<Panel>,
+    // collector name to how often it runs
+    pub collectors: BTreeMap<String, Duration>,
+    pub thresholds: Thresholds,
+    pub charts: Charts,
+    pub keys: Keymap,
+    pub theme: Theme,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            interval: Duration::from_millis(500),
+            render_rate: Duration::from_millis(250),
+            history: 300,
+            units: Units::Celcius,
+            panels: Panel::value_variants().to_vec(),
+            collectors: BTreeMap::new(),
+            thresholds: Thresholds {
+                usage: Levels {
+                    warn: 75.0,
+                    crit: 90.0,
+                },
+                psi: Levels {
+                    warn: 5.0,
+                    crit: 25.0,
+                },
+                battery: Levels {
+                    warn: 65.0,
+                    crit: 25.0,
+                },
+            },
+            charts: Charts {
+                limits: true,
+                styles: HashMap::new(),
+            },
+            keys: Keymap::default(),
+            theme: Theme {
+                ok: Color::LightGreen,
+                warn: Color::LightYellow,
+                crit: Color::LightRed,
+                footer: Color::LightYellow,
+            },
+        }
+    }
+}
+
+impl Config {
+    pub fn usage_color(&self, percent: f64) -> Color {
+        self.level_color(percent, self.thresholds.usage)
+    }
+
+    pub fn psi_color(&self, avg: f64) -> Color {
+        self.level_color(avg, self.thresholds.psi)
+    }
+
+    pub fn chart_style(&self, chart: HistoryChart) -> GraphType {
+        self.charts
+            .styles
+            .get(&chart)
+            .copied()
+            .unwrap_or(match chart {
+                HistoryChart::Disks | HistoryChart::Power => GraphType::SparkLine,
+                _ => GraphType::Braille,
+            })
+    }
+
+    pub fn battery_color(&self, percent: f64) -> Color {
+        let levels = self.thresholds.battery;
+        if percent < levels.crit {
+            self.theme.crit
+        } else if percent < levels.warn {
+            self.theme.warn
+        } else {
+            self.theme.ok
+        }
+    }
+
+    fn level_color(&self, value: f64, levels: Levels) -> Color {
+        if value >= levels.crit {
+            self.theme.crit
+        } else if value >= levels.warn {
+            self.theme.warn
+        } else {
+            self.theme.ok
+        }
+    }
+
+    // what the poller needs to know
+    pub fn schedule(&self) -> Schedule {
+        Schedule {
+            interval: self.interval,
+            collectors: self.collectors.clone(),
+        }
+    }
+
+    // command line flags win over the file
+    pub fn override_with(&mut self, args: &Args) {
+        if let Some(interval) = args.interval {
+            self.interval = interval;
+        }
+        if let Some(unit) = args.unit {
+            self.units = unit;
+        }
+        if let Some(panels) = &args.panels {
+            self.panels = panels.clone();
+        }
+        if let Some(history) = args.history {
+            self.history = history as usize;
+        }
+    }
+
+    // Check a config file and fill in the defaults around it
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let file: File = toml::from_str(text).map_err(|e| {
+            // the full error draws a snippet over several lines, the status line has one
+            let line = e
+                .span()
+                .map(|span| text[..span.start].matches('\n').count() + 1);
+            match line {
+                Some(line) => format!("line {}: {}", line, e.message().trim()),
+                None => e.message().trim().to_owned(),
+            }
+        })?;
+        let mut config = Config::default();
+
+        let duration = |name: &str, text: &Option<String>, default: Duration| match text {
+            Some(text) => parse_interval(text).map_err(|e| format!("{}: {}", name, e)),
+            None => Ok(default),
+        };
+        config.interval = duration("interval", &file.interval, config.interval)?;
+        config.render_rate = duration("render_rate", &file.render_rate, config.render_rate)?;
+        if let Some(history) = file.history {
+            if history < 2 {
+                return Err("history: keep at least 2 points".to_owned());
+            }
+            config.history = history as usize;
+        }
+        if let Some(unit) = &file.unit {
+            config.units = Units::from_str(unit, true)
+                .map_err(|_| format!("unit: '{}' is not c or f", unit))?;
+        }
+        if let Some(panels) = &file.panels {
+            config.panels = panels
+                .iter()
+                .map(|p| {
+                    Panel::from_str(p, true).map_err(|_| {
+                        let names: Vec<String> = Panel::value_variants()
+                            .iter()
+                            .filter_map(|v| Some(v.to_possible_value()?.get_name().to_owned()))
+                            .collect();
+                        format!("panels: no panel '{}', try {}", p, names.join(", "))
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+        }
+
+        let known = Registry::with_defaults().names();
+        for (name, interval) in &file.collectors {
+            if !known.contains(&name.as_str()) {
+                return Err(format!(
+                    "collectors: no collector '{}', try {}",
+                    name,
+                    known.join(", ")
+                ));
+            }
+            let interval =
+                parse_duration(interval).map_err(|e| format!("collectors.{}: {}", name, e))?;
+            config.collectors.insert(name.clone(), interval);
+        }
+
+        let thresholds = &mut config.thresholds;
+        for (name, levels, slot, rising) in [
+            ("usage", file.thresholds.usage, &mut thresholds.usage, true),
+            ("psi", file.thresholds.psi, &mut thresholds.psi, true),
+            (
+                "battery",
+                file.thresholds.battery,
+                &mut thresholds.battery,
+                false,
+            ),
+        ] {
+            let Some(levels) = levels else {
+                continue;
+            };
+            let in_range = |v: f64| (0.0..=100.0).contains(&v);
+            if !in_range(levels.warn) || !in_range(levels.crit) {
+                return Err(format!(
+                    "thresholds.{}: levels are percents, 0 to 100",
+                    name
+                ));
+            }
+            if rising && levels.warn > levels.crit {
+                return Err(format!("thresholds.{}: warn has to be below crit", name));
+            }
+            if !rising && levels.warn < levels.crit {
+                return Err(format!(
+                    "thresholds.{}: the battery counts down, warn has to be above crit",
+                    name
+                ));
+            }
+            *slot = levels;
+        }
+
+        if let Some(limits) = file.charts.limits {
+            config.charts.limits = limits;
+        }
+        for (chart, style) in [
+            (HistoryChart::Temp, &file.charts.temp),
+            (HistoryChart::Memory, &file.charts.memory),
+            (HistoryChart::Network, &file.charts.network),
+            (HistoryChart::Battery, &file.charts.battery),
+            (HistoryChart::Disks, &file.charts.disks),
+            (HistoryChart::Power, &file.charts.power),
+        ] {
+            if let Some(style) = style {
+                let style = GraphType::from_str(style, true).map_err(|_| {
+                    format!(
+                        "charts.{}: no style '{}', try line, scatter, braille, sparkline or bar",
+                        chart.name(),
+                        style
+                    )
+                })?;
+                config.charts.styles.insert(chart, style);
+            }
+        }
+
+        // The same key on two actions is almost always a mistake. That counts
+        // the defaults of every action the file leaves alone, or binding quit
+        // to esc would quietly take it off cancel
+        let mut taken: HashMap<KeyCode, (&str, bool)> = default_keys()
+            .filter(|(action, _)| !file.keys.contains_key(*action))
+            .map(|(action, code)| (code, (action, true)))
+            .collect();
+        for (action, keys) in &file.keys {
+            let keys = match keys {
+                FileKeys::One(key) => vec![key.clone()],
+                FileKeys::Many(keys) => keys.clone(),
+            };
+            let mut codes = Vec::new();
+            for key in keys {
+                let code = parse_key(&key)
+                    .ok_or_else(|| format!("keys.{}: unknown key '{}'", action, key))?;
+                match taken.insert(code, (action, false)) {
+                    Some((other, _)) if other == action => {}
+                    Some((other, true)) => {
+                        return Err(format!(
+                            "keys: '{}' is already {}'s key, give {} other keys too",
+                            key, other, other
+                        ))
+                    }
+                    Some((other, false)) => {
+                        return Err(format!(
+                            "keys: '{}' is bound to both {} and {}",
+                            key, other, action
+                        ))
+                    }
+                    None => {}
+                }
+                codes.push(code);
+            }
+            config
+                .keys
+                .bind(action, &codes)
+                .map_err(|e| format!("keys: {}", e))?;
+        }
+
+        let theme = &mut config.theme;
+        for (name, color, slot) in [
+            ("ok", &file.theme.ok, &mut theme.ok),
+            ("warn", &file.theme.warn, &mut theme.warn),
+            ("crit", &file.theme.crit, &mut theme.crit),
+            ("footer", &file.theme.footer, &mut theme.footer),
+        ] {
+            if let Some(color) = color {
+                *slot = Color::from_str(color).map_err(|_| {
+                    format!(
+                        "theme.{}: '{}' is not a color, try a name like lightred or #rrggbb",
+                        name, color
+                    )
+                })?;
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+// the file as written, Config::parse checks it
+#[derive(Default, Deserialize)]
+#[serde(crate = "the_serde", default, deny_unknown_fields)]
+struct File {
+    interval: Option<String>,
+    render_rate: Option<String>,
+    history: Option<u64>,
+    unit: Option<String>,
+    panels: Option<Vec<String>>,
+    collectors: BTreeMap<String, String>,
+    thresholds: FileThresholds,
+    charts: FileCharts,
+    keys: BTreeMap<String, FileKeys>,
+    theme: FileTheme,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(crate = "the_serde", default, deny_unknown_fields)]
+struct FileThresholds {
+    usage: Option<Levels>,
+    psi: Option<Levels>,
+    battery: Option<Levels>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(crate = "the_serde", default, deny_unknown_fields)]
+struct FileCharts {
+    limits: Option<bool>,
+    temp: Option<String>,
+    memory: Option<String>,
+    network: Option<String>,
+    battery: Option<String>,
+    disks: Option<String>,
+    power: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(crate = "the_serde", default, deny_unknown_fields)]
+struct FileTheme {
+    ok: Option<String>,
+    warn: Option<String>,
+    crit: Option<String>,
+    footer: Option<String>,
+}
+
+// quit = "q" or quit = ["q", "esc"]
+#[derive(Deserialize)]
+#[serde(crate = "the_serde", untagged)]
+enum FileKeys {
+    One(String),
+    Many(Vec<String>),
+}
+
+// "250ms", "2s", "1.5s" or a bare number of milliseconds
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let (number, scale) = if let Some(ms) = text.strip_suffix("ms") {
+        (ms, 0.001)
+    } else if let Some(s) = text.strip_suffix('s') {
+        (s, 1.0)
+    } else {
+        (text, 0.001)
+    };
+    let secs = number
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("'{}' is not a time, try 500ms or 2s", text))?
+        * scale;
+    if !(0.001..=3600.0).contains(&secs) {
+        return Err("times have to be between 1ms and an hour".to_owned());
+    }
+    Ok(Duration::from_secs_f64(secs))
+}
+
+// A sample interval or render rate, kept to the range '+' and '-' step
+// through so nothing polls /proc a thousand times a second
+pub fn parse_interval(text: &str) -> Result<Duration, String> {
+    let interval = parse_duration(text)?;
+    let (min, max) = (INTERVALS[0], INTERVALS[INTERVALS.len() - 1]);
+    if !(min..=max).contains(&(interval.as_millis() as u64)) {
+        return Err(format!(
+            "'{}' is out of range, use {} to {}",
+            text.trim(),
+            format_interval(Duration::from_millis(min)),
+            format_interval(Duration::from_millis(max))
+        ));
+    }
+    Ok(interval)
+}
+
+// $XDG_CONFIG_HOME/sys-mon/config.toml, falling back to ~/.config
+pub fn default_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("sys-mon").join("config.toml"))
+}
+
+// the file --config names or the default one
+pub fn path(args: &Args) -> Option<PathBuf> {
+    args.config.clone().or_else(default_path)
+}
+
+// Read and check the file with the command line on top. Not having one at the
+// default spot just means defaults, but a file asked for by name has to be there
+pub fn load(args: &Args) -> Result<Config, String> {
+    let mut config = match path(args) {
+        Some(path) => match fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && args.config.is_none() => {
+                Config::default()
+            }
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        },
+        None => Config::default(),
+    };
+    config.override_with(args);
+    Ok(config)
+}
+
+// Put a chart style picked with 'g' in the file so it sticks. The rest of
+// the file, comments and all, stays as it was
+pub fn save_chart_style(path: &Path, chart: HistoryChart, style: GraphType) -> Result<(), String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let mut doc = text
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| format!("{}: {}", path.display(), e.message()))?;
+    let charts = doc
+        .entry("charts")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or_else(|| format!("{}: charts is not a table", path.display()))?;
+    charts.insert(chart.name(), toml_edit::value(style.name()));
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    fs::write(path, doc.to_string()).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Notices the file being written, created or deleted. Polling the mtime
+// also catches editors that save by renaming a new file over the old one
+pub struct Watcher {
+    path: PathBuf,
+    seen: Option<SystemTime>,
+}
+
+impl Watcher {
+    pub fn new(path: PathBuf) -> Self {
+        let seen = modified(&path);
+        Watcher { path, seen }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn changed(&mut self) -> bool {
+        let now = modified(&self.path);
+        if now == self.seen {
+            return false;
+        }
+        self.seen = now;
+        true
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn parses_a_full_file() {
+        let config = Config::parse(
+            r##"
+            interval = "2s"
+            unit = "f"
+            panels = ["load", "memory-history"]
+
+            [collectors]
+            mounts = "30s"
+
+            [thresholds]
+            usage = { warn = 50, crit = 80 }
+
+            [charts]
+            limits = false
+            network = "sparkline"
+
+            [keys]
+            quit = ["x", "Q"]
+
+            [theme]
+            crit = "#ff0000"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.interval, Duration::from_secs(2));
+        assert!(config.units == Units::Fahrenheit);
+        assert!(config.panels == [Panel::Load, Panel::MemoryHistory]);
+        assert_eq!(config.collectors["mounts"], Duration::from_secs(30));
+        assert_eq!(config.usage_color(85.0), Color::Rgb(255, 0, 0));
+        assert_eq!(config.usage_color(60.0), Color::LightYellow);
+        assert_eq!(config.history, 300);
+        assert!(!config.charts.limits);
+        assert!(config.chart_style(HistoryChart::Network) == GraphType::SparkLine);
+        assert!(config.chart_style(HistoryChart::Temp) == GraphType::Braille);
+        assert!(config.chart_style(HistoryChart::Disks) == GraphType::SparkLine);
+        assert!(config.keys.get(KeyCode::Char('x')).is_some());
+        assert!(config.keys.get(KeyCode::Char('q')).is_none());
+        // the footer names the rebound key, and shows a letter in both cases once
+        use crate::events::KeyActions;
+        assert_eq!(config.keys.names(KeyActions::Quit), ["Q", "x"]);
+        assert_eq!(config.keys.names(KeyActions::Kill), ["K"]);
+        assert_eq!(config.keys.names(KeyActions::Confirm), ["ENTER", "Y"]);
+    }
+
+    #[test]
+    fn says_what_is_wrong() {
+        let error = |text: &str| Config::parse(text).err().unwrap();
+        assert_eq!(
+            error("interval = \"soon\""),
+            "interval: 'soon' is not a time, try 500ms or 2s"
+        );
+        assert!(error("\n\nintervall = \"1s\"").starts_with("line 3: unknown field `intervall`"));
+        assert!(error("[collectors]\nnope = \"1s\"").starts_with("collectors: no collector 'nope'"));
+        assert_eq!(
+            error("[thresholds]\nbattery = { warn = 10, crit = 20 }"),
+            "thresholds.battery: the battery counts down, warn has to be above crit"
+        );
+        assert_eq!(
+            error("[keys]\nquit = \"x\"\nkill = \"x\""),
+            "keys: 'x' is bound to both kill and quit"
+        );
+        assert!(
+            error("[theme]\nok = \"blurple\"").starts_with("theme.ok: 'blurple' is not a color")
+        );
+        assert!(error("[keys]\nexplode = \"x\"").starts_with("keys: unknown action 'explode'"));
+        assert_eq!(
+            error("[keys]\nquit = [\"x\", \"esc\"]"),
+            "keys: 'esc' is already cancel's key, give cancel other keys too"
+        );
+        assert_eq!(
+            error("[keys]\nunits = \"TAB\"\nsort = \"tab\""),
+            "keys: 'TAB' is bound to both sort and units"
+        );
+        assert!(Config::parse("[keys]\nquit = \"esc\"\ncancel = \"x\"").is_ok());
+        assert!(error("[charts]\ntemp = \"pie\"").starts_with("charts.temp: no style 'pie'"));
+    }
+
+    #[test]
+    fn example_file_is_valid() {
+        let example = include_str!("../assets/config.toml");
+        Config::parse(example).unwrap();
+    }
+
+    #[test]
+    fn saves_chart_styles() {
+        let path = std::env::temp_dir().join(format!("sys-mon-{}.toml", std::process::id()));
+        fs::write(&path, "# mine\nunit = \"f\"\n").unwrap();
+        save_chart_style(&path, HistoryChart::Temp, GraphType::Bar).unwrap();
+        save_chart_style(&path, HistoryChart::Temp, GraphType::Line).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(text.starts_with("# mine\n"));
+        let config = Config::parse(&text).unwrap();
+        assert!(config.units == Units::Fahrenheit);
+        assert!(config.chart_style(HistoryChart::Temp) == GraphType::Line);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2000"), Ok(Duration::from_secs(2)));
+        assert!(parse_duration("0").is_err());
+        assert_eq!(
+            parse_interval("1ms"),
+            Err("'1ms' is out of range, use 100ms to 30s".to_owned())
+        );
+        assert_eq!(parse_interval("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_interval("100ms"), Ok(Duration::from_millis(100)));
+        assert!(parse_interval("99ms").is_err());
+        assert!(parse_interval("31s").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+/*
+- CPU frequency scaling and thermal throttle counters from sysfs
+*/
+use crate::collector::{Collector, Sample};
+use crate::sysfs::{self, read_number, read_text};
+use std::io;
+use std::path::Path;
+use systemstat::System;
+
+const CPU_ROOT: &str = "/sys/devices/system/cpu";
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct CoreFreq {
+    pub cpu: u32,
+    pub cur_mhz: f64,
+    pub min_mhz: f64,
+    pub max_mhz: f64,
+    pub governor: Option<String>,
+    // only intel exposes these, and they never go down
+    pub core_throttles: Option<u64>,
+    pub package_throttles: Option<u64>,
+}
+
+// One entry per cpu that has cpufreq, sorted by cpu number
+pub fn read_freqs() -> Vec<CoreFreq> {
+    read_freqs_at(Path::new(CPU_ROOT))
+}
+
+pub struct FreqCollector;
+
+impl Collector for FreqCollector {
+    fn name(&self) -> &'static str {
+        "cpufreq"
+    }
+
+    fn collect(&mut self, _sys: &System) -> io::Result<Sample> {
+        let freqs = read_freqs();
+        // VMs and some ARM boards have no cpufreq at all
+        if freqs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no cpufreq policies in sysfs",
+            ));
+        }
+        Ok(Sample::Freqs(freqs))
+    }
+}
+
+// Summary of all cores for the overview
+pub struct FreqSummary {
+    pub avg_mhz: f64,
+    pub min_mhz: f64,
+    pub max_mhz: f64,
+    pub governor: String,
+    pub throttles: Option<u64>,
+}
+
+pub fn summarize(freqs: &[CoreFreq]) -> Option<FreqSummary> {
+    if freqs.is_empty() {
+        return None;
+    }
+    let governor = match &freqs[0].governor {
+        Some(g) if freqs.iter().all(|f| f.governor.as_ref() == Some(g)) => g.clone(),
+        Some(_) => "mixed".to_owned(),
+        None => "NA".to_owned(),
+    };
+    // package counters are shared by every core in the package so only count them once
+    let core: Option<u64> = freqs
+        .iter()
+        .filter_map(|f| f.core_throttles)
+        .reduce(|a, b| a + b);
+    let package = freqs.iter().filter_map(|f| f.package_throttles).max();
+    Some(FreqSummary {
+        avg_mhz: freqs.iter().map(|f| f.cur_mhz).sum::<f64>() / freqs.len() as f64,
+        min_mhz: freqs.iter().map(|f| f.min_mhz).fold(f64::MAX, f64::min),
+        max_mhz: freqs.iter().map(|f| f.max_mhz).fold(0.0, f64::max),
+        governor,
+        throttles: match (core, package) {
+            (None, None) => None,
+            (c, p) => Some(c.unwrap_or(0) + p.unwrap_or(0)),
+        },
+    })
+}
+
+fn read_khz(path: &Path) -> Option<f64> {
+    read_number(path).map(|khz: f64| khz / 1000.0)
+}
+
+fn read_freqs_at(root: &Path) -> Vec<CoreFreq> {
+    let mut freqs: Vec<CoreFreq> = sysfs::dirs(root)
+        .into_iter()
+        .filter_map(|(name, dir)| {
+            let cpu: u32 = name.strip_prefix("cpu")?.parse().ok()?;
+            let freq = dir.join("cpufreq");
+            let throttle = dir.join("thermal_throttle");
+            let count = |file: &str| read_number(&throttle.join(file));
+            Some(CoreFreq {
+                cpu,
+                // scaling_cur_freq is what the governor asked for, close enough and always readable
+                cur_mhz: read_khz(&freq.join("scaling_cur_freq"))?,
+                min_mhz: read_khz(&freq.join("cpuinfo_min_freq")).unwrap_or(0.0),
+                max_mhz: read_khz(&freq.join("cpuinfo_max_freq")).unwrap_or(0.0),
+                governor: read_text(&freq.join("scaling_governor")),
+                core_throttles: count("core_throttle_count"),
+                package_throttles: count("package_throttle_count"),
+            })
+        })
+        .collect();
+    freqs.sort_by_key(|f| f.cpu);
+    freqs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_freqs_at, summarize};
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn freqs_and_throttles() {
+        let root = TestDir::new("cpufreq");
+        for (cpu, cur, throttles) in [(1, "2400000", "3"), (0, "1200000", "2")] {
+            let file = |name: &str| format!("cpu{}/{}", cpu, name);
+            root.write(&file("cpufreq/scaling_cur_freq"), cur);
+            root.write(&file("cpufreq/cpuinfo_min_freq"), "800000");
+            root.write(&file("cpufreq/cpuinfo_max_freq"), "4000000");
+            root.write(&file("cpufreq/scaling_governor"), "powersave\n");
+            root.write(&file("thermal_throttle/core_throttle_count"), throttles);
+            root.write(&file("thermal_throttle/package_throttle_count"), "10");
+        }
+        // not a cpu
+        root.write("cpuidle/state", "");
+
+        let freqs = read_freqs_at(&root);
+
+        assert_eq!(freqs.len(), 2);
+        assert_eq!(freqs[0].cpu, 0);
+        assert_eq!(freqs[0].cur_mhz, 1200.0);
+        let summary = summarize(&freqs).unwrap();
+        assert_eq!(summary.avg_mhz, 1800.0);
+        assert_eq!(summary.min_mhz, 800.0);
+        assert_eq!(summary.max_mhz, 4000.0);
+        assert_eq!(summary.governor, "powersave");
+        assert_eq!(summary.throttles, Some(15));
+    }
+}
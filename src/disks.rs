@@ -0,0 +1,115 @@
+/*
+- Block device throughput. The kernel only gives us counters that go up
+- forever so we keep the last sample around and turn the difference into rates
+*/
+use crate::collector::{Collector, Sample};
+use std::collections::BTreeMap;
+use std::io;
+use std::time::Instant;
+use systemstat::{BlockDeviceStats, Platform, System};
+
+// /proc/diskstats always counts in 512 byte sectors no matter the device
+const SECTOR_SIZE: f64 = 512.0;
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct DiskIo {
+    pub name: String,
+    pub read_bps: f64,
+    pub write_bps: f64,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    // percent of the interval the device had io in flight
+    pub busy: f64,
+}
+
+impl DiskIo {
+    fn between(prev: &BlockDeviceStats, now: &BlockDeviceStats, secs: f64) -> Self {
+        let delta = |a: usize, b: usize| b.saturating_sub(a) as f64;
+        DiskIo {
+            name: now.name.clone(),
+            read_bps: delta(prev.read_sectors, now.read_sectors) * SECTOR_SIZE / secs,
+            write_bps: delta(prev.write_sectors, now.write_sectors) * SECTOR_SIZE / secs,
+            read_iops: delta(prev.read_ios, now.read_ios) / secs,
+            write_iops: delta(prev.write_ios, now.write_ios) / secs,
+            // io_ticks is in milliseconds
+            busy: (delta(prev.io_ticks, now.io_ticks) / (secs * 10.0)).min(100.0),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct DiskSampler {
+    prev: BTreeMap<String, BlockDeviceStats>,
+    taken: Option<Instant>,
+}
+
+impl DiskSampler {
+    // Rates since the last call. The first call only primes the counters and
+    // returns nothing. Devices that have never done any io are skipped.
+    pub fn sample(&mut self, sys: &System) -> io::Result<Vec<DiskIo>> {
+        let stats = sys.block_device_statistics()?;
+        let now = Instant::now();
+
+        let mut disks = Vec::new();
+        if let Some(taken) = self.taken {
+            let secs = now.duration_since(taken).as_secs_f64().max(0.001);
+            for stat in stats.values() {
+                if stat.read_ios + stat.write_ios == 0 {
+                    continue;
+                }
+                if let Some(prev) = self.prev.get(&stat.name) {
+                    disks.push(DiskIo::between(prev, stat, secs));
+                }
+            }
+        }
+
+        self.prev = stats;
+        self.taken = Some(now);
+        Ok(disks)
+    }
+}
+
+impl Collector for DiskSampler {
+    fn name(&self) -> &'static str {
+        "disks"
+    }
+
+    fn collect(&mut self, sys: &System) -> io::Result<Sample> {
+        self.sample(sys).map(Sample::Disks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiskIo;
+    use systemstat::BlockDeviceStats;
+
+    fn stats(sectors: usize, ios: usize, ticks: usize) -> BlockDeviceStats {
+        BlockDeviceStats {
+            name: "sda".to_owned(),
+            read_ios: ios,
+            read_merges: 0,
+            read_sectors: sectors,
+            read_ticks: 0,
+            write_ios: ios,
+            write_merges: 0,
+            write_sectors: sectors,
+            write_ticks: 0,
+            in_flight: 0,
+            io_ticks: ticks,
+            time_in_queue: 0,
+        }
+    }
+
+    #[test]
+    fn rates_between_samples() {
+        let disk = DiskIo::between(&stats(0, 0, 0), &stats(2048, 20, 500), 2.0);
+        assert_eq!(disk.read_bps, 512.0 * 1024.0);
+        assert_eq!(disk.write_iops, 10.0);
+        assert_eq!(disk.busy, 25.0);
+    }
+}
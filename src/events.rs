@@ -1,59 +1,271 @@
 /*
     listener for  key presses
 */
-use crossterm;
-use crossterm::event::KeyCode;
-use std::sync::mpsc;
+use crate::app::View;
+use crossterm::event::{Event as CrosstermEvent, EventStream, KeyCode, KeyEventKind};
+use futures::StreamExt;
+use std::collections::HashMap;
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum KeyActions {
     Quit,
     ToggleUnits,
     ClearTemp,
+    SwitchView(View),
+    Up,
+    Down,
+    TogglePseudo,
+    SlowerSampling,
+    FasterSampling,
+    CycleFreqChart,
+    CycleGraph,
+    FocusChart,
+    WiderWindow,
+    NarrowerWindow,
+    CycleSort,
+    ReverseSort,
+    ToggleTree,
+    StartFilter,
+    Terminate,
+    Kill,
+    StopContinue,
+    Renice,
+    Confirm,
+    Cancel,
+    // only sent while the app is taking text input
+    Input(char),
+    InputBackspace,
+    InputDone,
+    InputCancel,
 }
+// what the terminal woke us up for
+pub enum Event {
+    Key(KeyActions),
+    // resized or focused, nothing to do but draw again
+    Redraw,
+}
+
+// every action that can be bound, by its name in the config file, with its default keys
+const BINDINGS: &[(&str, KeyActions, &[&str])] = &[
+    ("units", KeyActions::ToggleUnits, &["tab"]),
+    ("quit", KeyActions::Quit, &["q", "Q"]),
+    ("clear", KeyActions::ClearTemp, &["c", "C"]),
+    ("overview", KeyActions::SwitchView(View::Overview), &["1"]),
+    ("mounts", KeyActions::SwitchView(View::Mounts), &["2"]),
+    ("disks", KeyActions::SwitchView(View::Disks), &["3"]),
+    ("network", KeyActions::SwitchView(View::Network), &["4"]),
+    ("processes", KeyActions::SwitchView(View::Processes), &["5"]),
+    ("memory", KeyActions::SwitchView(View::Memory), &["6"]),
+    ("sensors", KeyActions::SwitchView(View::Sensors), &["7"]),
+    ("battery", KeyActions::SwitchView(View::Battery), &["8"]),
+    ("up", KeyActions::Up, &["up"]),
+    ("down", KeyActions::Down, &["down"]),
+    ("pseudo", KeyActions::TogglePseudo, &["p", "P"]),
+    // '=' is '+' without shift on most layouts
+    ("slower", KeyActions::SlowerSampling, &["+", "="]),
+    ("faster", KeyActions::FasterSampling, &["-"]),
+    ("freq_chart", KeyActions::CycleFreqChart, &["f", "F"]),
+    ("style", KeyActions::CycleGraph, &["g", "G"]),
+    ("chart", KeyActions::FocusChart, &["h", "H"]),
+    ("wider", KeyActions::WiderWindow, &["]"]),
+    ("narrower", KeyActions::NarrowerWindow, &["["]),
+    ("sort", KeyActions::CycleSort, &["s", "S"]),
+    ("reverse", KeyActions::ReverseSort, &["r", "R"]),
+    ("tree", KeyActions::ToggleTree, &["t", "T"]),
+    ("filter", KeyActions::StartFilter, &["/"]),
+    // case matters here, a capital K is the one that cant be caught
+    ("terminate", KeyActions::Terminate, &["k"]),
+    ("kill", KeyActions::Kill, &["K"]),
+    ("stop", KeyActions::StopContinue, &["z", "Z"]),
+    ("renice", KeyActions::Renice, &["n", "N"]),
+    ("confirm", KeyActions::Confirm, &["enter", "y", "Y"]),
+    ("cancel", KeyActions::Cancel, &["esc"]),
+];
+
+// What each key does outside of text input
+#[derive(Clone)]
+pub struct Keymap {
+    keys: HashMap<KeyCode, KeyActions>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keys = HashMap::new();
+        for (_, action, defaults) in BINDINGS {
+            for key in defaults.iter() {
+                keys.insert(parse_key(key).expect("default key"), *action);
+            }
+        }
+        Keymap { keys }
+    }
+}
+
+impl Keymap {
+    // Put an action on these keys instead of its defaults. A key that did
+    // something else before does this now
+    pub fn bind(&mut self, action: &str, keys: &[KeyCode]) -> Result<(), String> {
+        let (_, action, _) = BINDINGS
+            .iter()
+            .find(|(name, _, _)| *name == action)
+            .ok_or_else(|| {
+                let names: Vec<&str> = BINDINGS.iter().map(|(name, _, _)| *name).collect();
+                format!(
+                    "unknown action '{}', try one of {}",
+                    action,
+                    names.join(", ")
+                )
+            })?;
+        self.keys.retain(|_, bound| bound != action);
+        for key in keys {
+            self.keys.insert(*key, *action);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, code: KeyCode) -> Option<KeyActions> {
+        self.keys.get(&code).copied()
+    }
+
+    // What to call an action's keys in the help text, defaults first. A letter
+    // bound in both cases only shows as the capital, like 'Q'
+    pub fn names(&self, action: KeyActions) -> Vec<String> {
+        let defaults: Vec<KeyCode> = BINDINGS
+            .iter()
+            .filter(|(_, a, _)| *a == action)
+            .flat_map(|(_, _, keys)| keys.iter().filter_map(|k| parse_key(k)))
+            .collect();
+        let mut keys: Vec<KeyCode> = self
+            .keys
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(k, _)| *k)
+            .collect();
+        keys.sort_by_key(|k| (defaults.iter().position(|d| d == k), key_name(*k)));
+        // None sorts first, put keys the user added after the defaults
+        keys.sort_by_key(|k| !defaults.contains(k));
+        let mut names: Vec<String> = Vec::new();
+        for key in &keys {
+            let name = match key {
+                KeyCode::Char(c) if keys.contains(&KeyCode::Char(c.to_ascii_uppercase())) => {
+                    c.to_ascii_uppercase().to_string()
+                }
+                _ => key_name(*key),
+            };
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+// how a key reads in the help text
+fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "SPACE".to_owned(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Tab => "TAB".to_owned(),
+        KeyCode::Enter => "ENTER".to_owned(),
+        KeyCode::Esc => "ESC".to_owned(),
+        KeyCode::Backspace => "BACKSPACE".to_owned(),
+        KeyCode::Up => "UP".to_owned(),
+        KeyCode::Down => "DOWN".to_owned(),
+        KeyCode::Left => "LEFT".to_owned(),
+        KeyCode::Right => "RIGHT".to_owned(),
+        KeyCode::Home => "HOME".to_owned(),
+        KeyCode::End => "END".to_owned(),
+        KeyCode::PageUp => "PAGEUP".to_owned(),
+        KeyCode::PageDown => "PAGEDOWN".to_owned(),
+        _ => "?".to_owned(),
+    }
+}
+
+// every default key with the name of the action it is on
+pub fn default_keys() -> impl Iterator<Item = (&'static str, KeyCode)> {
+    BINDINGS.iter().flat_map(|(name, _, keys)| {
+        keys.iter()
+            .map(move |key| (*name, parse_key(key).expect("default key")))
+    })
+}
+
+// "q", "K", "tab", "enter" and friends
+pub fn parse_key(text: &str) -> Option<KeyCode> {
+    let mut chars = text.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    Some(match text.to_lowercase().as_str() {
+        "tab" => KeyCode::Tab,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => return None,
+    })
+}
+
 pub struct KeyPressHandler {
-    tick_rate: std::time::Duration,
-    sender: mpsc::Sender<Option<KeyActions>>,
+    stream: EventStream,
+    keymap: Keymap,
 }
 
 impl KeyPressHandler {
-    pub fn new(sender: mpsc::Sender<Option<KeyActions>>) -> Self {
+    pub fn new(keymap: Keymap) -> Self {
         KeyPressHandler {
-            sender,
-            tick_rate: std::time::Duration::from_millis(250),
+            stream: EventStream::new(),
+            keymap,
         }
     }
 
-    pub fn poll(&mut self) {
-        let mut channel_status = Ok(());
-
-        // loop until the channel tries to send into poop
-        while channel_status.is_ok() {
-            // Poll until timeout so we dont hang forever
-            if crossterm::event::poll(self.tick_rate).is_ok() {
-                // if the read gets something it should be an OK so match it
-                if let Ok(crossterm::event::Event::Key(key)) = crossterm::event::read() {
-                    // I guess this is for if its a keypress
-                    if key.kind == crossterm::event::KeyEventKind::Press {
-                        // We only care about tab and q
-                        match key.code {
-                            KeyCode::Tab => {
-                                channel_status = self.sender.send(Some(KeyActions::ToggleUnits))
-                            }
-                            KeyCode::Char('q') | KeyCode::Char('Q') => {
-                                channel_status = self.sender.send(Some(KeyActions::Quit))
-                            }
-                            KeyCode::Char('C') | KeyCode::Char('c') => {
-                                channel_status = self.sender.send(Some(KeyActions::ClearTemp))
-                            }
-                            //Just send None if its a key we dont care about
-                            _ => channel_status = self.sender.send(None),
-                        }
+    // new bindings after the config file changed
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    // The next event worth waking the app for. None once the terminal is gone.
+    // Safe to drop mid wait in a select, nothing is lost between awaits
+    pub async fn next(&mut self, typing: bool) -> Option<Event> {
+        loop {
+            match self.stream.next().await? {
+                // I guess this is for if its a keypress
+                Ok(CrosstermEvent::Key(key)) if key.kind == KeyEventKind::Press => {
+                    // keys we dont care about dont wake anyone up
+                    if let Some(action) = map_key(key.code, typing, &self.keymap) {
+                        return Some(Event::Key(action));
                     }
-                } else {
-                    // Just send nothing so we can not hang the listener thread
-                    channel_status = self.sender.send(None);
                 }
+                Ok(CrosstermEvent::Resize(..)) | Ok(CrosstermEvent::FocusGained) => {
+                    return Some(Event::Redraw)
+                }
+                Ok(_) => {}
+                Err(_) => return None,
             }
         }
     }
 }
+
+// map the keys we care about to actions
+fn map_key(code: KeyCode, typing: bool, keymap: &Keymap) -> Option<KeyActions> {
+    // while a filter is being typed every character is text
+    if typing {
+        return match code {
+            KeyCode::Char(c) => Some(KeyActions::Input(c)),
+            KeyCode::Backspace => Some(KeyActions::InputBackspace),
+            KeyCode::Enter => Some(KeyActions::InputDone),
+            KeyCode::Esc => Some(KeyActions::InputCancel),
+            KeyCode::Up => Some(KeyActions::Up),
+            KeyCode::Down => Some(KeyActions::Down),
+            _ => None,
+        };
+    }
+
+    keymap.get(code)
+}
@@ -0,0 +1,219 @@
+/*
+- Rolling history for every metric the charts draw, against wall clock time.
+  Each series keeps its newest points as they came in and averages older ones
+  down a level at a time, so a long session still fits in a fixed amount of
+  memory and the charts never empty out
+*/
+use std::collections::{HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// how many points get averaged into one on the next level down
+const FACTOR: usize = 10;
+// full resolution plus two averaged levels. At 500ms and 300 points that is
+// 2.5 minutes as sampled, 25 minutes at 5s and a bit over 4 hours at 50s
+const LEVELS: usize = 3;
+
+// seconds since the epoch, what every series is plotted against
+pub fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+// Local wall clock time of a point for the axis labels. The time crate cant
+// find the local offset once there are threads, so ask libc
+pub fn clock(time: f64, seconds: bool) -> String {
+    let secs = time as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+        return String::new();
+    }
+    if seconds {
+        format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec)
+    } else {
+        format!("{:02}:{:02}", tm.tm_hour, tm.tm_min)
+    }
+}
+
+#[derive(Default)]
+struct Level {
+    points: VecDeque<(f64, f64)>,
+    // pushed out of this level, waiting for enough to average into the next
+    spill: Vec<(f64, f64)>,
+}
+
+// One metric over time, at most len points per level
+pub struct Series {
+    levels: Vec<Level>,
+    len: usize,
+}
+
+impl Series {
+    pub fn new(len: usize) -> Self {
+        Series {
+            levels: (0..LEVELS).map(|_| Level::default()).collect(),
+            len,
+        }
+    }
+
+    pub fn push(&mut self, time: f64, value: f64) {
+        let mut point = Some((time, value));
+        let last = self.levels.len() - 1;
+        for (i, level) in self.levels.iter_mut().enumerate() {
+            let Some(p) = point.take() else {
+                break;
+            };
+            level.points.push_back(p);
+            if level.points.len() <= self.len {
+                break;
+            }
+            let old = level.points.pop_front().expect("level over its length");
+            // the last level just forgets
+            if i == last {
+                break;
+            }
+            level.spill.push(old);
+            if level.spill.len() == FACTOR {
+                point = Some(average(&level.spill));
+                level.spill.clear();
+            }
+        }
+    }
+
+    // Every point, oldest first. Older stretches are averaged
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        let mut points = Vec::new();
+        // a level's spill sits between the level below it and its own points
+        for level in self.levels.iter().rev() {
+            points.extend(level.spill.iter());
+            points.extend(level.points.iter());
+        }
+        points
+    }
+
+    fn first(&self) -> Option<(f64, f64)> {
+        self.levels
+            .iter()
+            .rev()
+            .find_map(|level| level.spill.first().or(level.points.front()))
+            .copied()
+    }
+
+    // history length changed in the config, drop the oldest to fit
+    fn set_len(&mut self, len: usize) {
+        self.len = len;
+        for level in &mut self.levels {
+            while level.points.len() > len {
+                level.points.pop_front();
+            }
+        }
+    }
+}
+
+fn average(points: &[(f64, f64)]) -> (f64, f64) {
+    let n = points.len() as f64;
+    let (time, value) = points
+        .iter()
+        .fold((0.0, 0.0), |(t, v), p| (t + p.0, v + p.1));
+    (time / n, value / n)
+}
+
+// Every series by name, like "mem", "sensor/<id>" or "net/<interface>/rx"
+pub struct History {
+    len: usize,
+    series: HashMap<String, Series>,
+}
+
+impl History {
+    pub fn new(len: usize) -> Self {
+        History {
+            len,
+            series: HashMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, key: &str, time: f64, value: f64) {
+        match self.series.get_mut(key) {
+            Some(series) => series.push(time, value),
+            None => {
+                let mut series = Series::new(self.len);
+                series.push(time, value);
+                self.series.insert(key.to_owned(), series);
+            }
+        }
+    }
+
+    // empty when nothing was recorded under that name
+    pub fn points(&self, key: &str) -> Vec<(f64, f64)> {
+        self.series.get(key).map(Series::points).unwrap_or_default()
+    }
+
+    // only the points at or after `from`
+    pub fn since(&self, key: &str, from: f64) -> Vec<(f64, f64)> {
+        let mut points = self.points(key);
+        points.retain(|p| p.0 >= from);
+        points
+    }
+
+    // time of the oldest point we still have in any series
+    pub fn oldest(&self) -> Option<f64> {
+        self.series
+            .values()
+            .filter_map(|s| s.first().map(|p| p.0))
+            .reduce(f64::min)
+    }
+
+    // forget devices that went away and anything the user cleared
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.series.retain(|key, _| keep(key));
+    }
+
+    pub fn set_len(&mut self, len: usize) {
+        self.len = len;
+        for series in self.series.values_mut() {
+            series.set_len(len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_old_points_and_stays_bounded() {
+        let mut series = Series::new(20);
+        for i in 0..10_000 {
+            series.push(i as f64, i as f64);
+        }
+        let points = series.points();
+        // every level full, plus whatever is waiting to be averaged
+        assert!(points.len() <= LEVELS * 20 + (LEVELS - 1) * FACTOR);
+        assert!(points.windows(2).all(|w| w[0].0 < w[1].0));
+        // the newest come through untouched
+        assert_eq!(points.last(), Some(&(9999.0, 9999.0)));
+        let newest = points.len() - 20;
+        assert_eq!(points[newest], (9980.0, 9980.0));
+        // one level down each point is the mean of ten
+        assert_eq!(points[newest - 1], (9974.5, 9974.5));
+
+        series.set_len(5);
+        series.push(10_000.0, 1.0);
+        assert!(series.points().len() <= LEVELS * 5 + (LEVELS - 1) * FACTOR);
+    }
+
+    #[test]
+    fn keeps_series_by_name() {
+        let mut history = History::new(3);
+        history.push("net/eth0/rx", 1.0, 10.0);
+        history.push("net/eth1/rx", 1.0, 20.0);
+        history.retain(|key| !key.starts_with("net/eth1/"));
+        assert_eq!(history.points("net/eth0/rx"), vec![(1.0, 10.0)]);
+        assert!(history.points("net/eth1/rx").is_empty());
+
+        history.push("net/eth0/rx", 5.0, 50.0);
+        assert_eq!(history.since("net/eth0/rx", 2.0), vec![(5.0, 50.0)]);
+        assert_eq!(history.oldest(), Some(1.0));
+    }
+}
@@ -0,0 +1,35 @@
+/*
+- The sampling side of sys-mon as a library. The TUI in main.rs is built on
+  this, other tools can use Poller for a stream of samples or snapshot() for one
+*/
+//! System metrics from procfs and sysfs.
+//!
+//! [`snapshot`] samples everything once and returns a [`Loads`]. For a steady
+//! stream run a [`Poller`] on a tokio runtime and [`Loads::apply`] each batch
+//! it sends.
+//!
+//! With the `serde` feature (on by default) [`Loads`] and every metric type
+//! implement `Serialize` and `Deserialize`. Byte counts come out as plain
+//! bytes, uptime in seconds and the boot time as a unix timestamp.
+pub mod battery;
+pub mod collector;
+pub mod cpufreq;
+pub mod disks;
+pub mod loads;
+pub mod memory;
+pub mod mounts;
+pub mod network;
+pub mod poller;
+pub mod procs;
+pub mod psi;
+pub mod rapl;
+pub mod sensors;
+#[cfg(feature = "serde")]
+mod serde_numbers;
+mod sysfs;
+#[cfg(test)]
+mod testdir;
+
+pub use collector::{Collected, Collector, Registry, Sample};
+pub use loads::{CoreLoad, Loads};
+pub use poller::{snapshot, snapshot_over, snapshot_with, Poller, Schedule};
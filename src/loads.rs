@@ -0,0 +1,341 @@
+/*
+- Everything the collectors know about the machine at the last tick. This is
+  what the TUI draws from and what library users get back from snapshot()
+*/
+use crate::battery::PowerSupplies;
+use crate::collector::{Collected, Sample};
+use crate::cpufreq::CoreFreq;
+use crate::disks::DiskIo;
+use crate::memory::MemInfo;
+use crate::mounts::MountInfo;
+use crate::network::Interface;
+use crate::procs::Process;
+use crate::psi::Psi;
+use crate::rapl::PowerZone;
+use crate::sensors::{Reading, TempSensor};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use systemstat::CPULoad;
+use time::OffsetDateTime;
+
+/// Load breakdown for the whole cpu or a single core, in percent.
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct CoreLoad {
+    pub user: f32,
+    pub nice: f32,
+    pub system: f32,
+    pub interrupt: f32,
+    pub idle: f32,
+}
+
+impl CoreLoad {
+    /// Everything that isnt idle.
+    pub fn busy(&self) -> f32 {
+        (100.0 - self.idle).clamp(0.0, 100.0)
+    }
+}
+
+impl From<&CPULoad> for CoreLoad {
+    fn from(cpu: &CPULoad) -> Self {
+        CoreLoad {
+            user: cpu.user * 100.0,
+            nice: cpu.nice * 100.0,
+            system: cpu.system * 100.0,
+            interrupt: cpu.interrupt * 100.0,
+            idle: cpu.idle * 100.0,
+        }
+    }
+}
+
+/// A snapshot of the machine built up from collector samples.
+///
+/// Anything a collector has not reported yet is `None` or empty. When a
+/// collector fails the last good data stays and the reason shows up in
+/// [`Loads::errors`] until it works again.
+#[derive(Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct Loads {
+    cpu: Option<CoreLoad>,
+    cores: Vec<CoreLoad>,
+    temp: Option<f32>,
+    sensors: Vec<TempSensor>,
+    readings: Vec<Reading>,
+    freqs: Vec<CoreFreq>,
+    psi: Option<Psi>,
+    power: PowerSupplies,
+    rapl: Vec<PowerZone>,
+    mem: Option<MemInfo>,
+    load_avg: Option<(f32, f32, f32)>,
+    // seconds, and the boot time as a unix timestamp, so scripts get numbers
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::opt_secs"))]
+    uptime: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(with = "time::serde::timestamp::option"))]
+    boot_time: Option<OffsetDateTime>,
+    host: Option<(String, String)>,
+    mounts: Option<Vec<MountInfo>>,
+    disks: Option<Vec<DiskIo>>,
+    network: Option<Vec<Interface>>,
+    processes: Option<Vec<Process>>,
+    errors: BTreeMap<String, String>,
+}
+
+impl Loads {
+    pub fn new() -> Loads {
+        Loads::default()
+    }
+
+    /// Fold one collector's result in.
+    pub fn apply(&mut self, collected: Collected) {
+        let sample = match collected.result {
+            Ok(sample) => {
+                self.errors.remove(collected.name);
+                sample
+            }
+            Err(e) => {
+                self.errors.insert(collected.name.to_owned(), e.to_string());
+                return;
+            }
+        };
+        match sample {
+            Sample::Cpu { total, cores } => {
+                self.cpu = Some(total);
+                self.cores = cores;
+            }
+            Sample::Sensors {
+                temps,
+                readings,
+                cpu_temp,
+            } => {
+                self.sensors = temps;
+                self.readings = readings;
+                self.temp = cpu_temp;
+            }
+            Sample::Freqs(freqs) => self.freqs = freqs,
+            Sample::Psi(psi) => self.psi = Some(psi),
+            Sample::Power(power) => self.power = power,
+            Sample::Rapl(zones) => self.rapl = zones,
+            Sample::Host {
+                load_avg,
+                uptime,
+                boot_time,
+                host,
+            } => {
+                self.load_avg = load_avg;
+                self.uptime = uptime;
+                self.boot_time = boot_time;
+                self.host = host;
+            }
+            Sample::Mounts(mounts) => self.mounts = Some(mounts),
+            Sample::Disks(disks) => self.disks = Some(disks),
+            Sample::Network(interfaces) => self.network = Some(interfaces),
+            Sample::Processes(processes) => self.processes = Some(processes),
+            Sample::Memory(mem) => self.mem = Some(mem),
+        }
+    }
+
+    /// Whole cpu load over the last sample interval.
+    pub fn cpu(&self) -> Option<CoreLoad> {
+        self.cpu
+    }
+
+    /// Per core load, in cpu order.
+    pub fn cores(&self) -> &[CoreLoad] {
+        &self.cores
+    }
+
+    /// The temperature that best stands for the cpu, in celsius.
+    pub fn cpu_temp(&self) -> Option<f32> {
+        self.temp
+    }
+
+    /// Every hwmon and thermal zone temperature.
+    pub fn temps(&self) -> &[TempSensor] {
+        &self.sensors
+    }
+
+    /// Fan, voltage and power readings from hwmon.
+    pub fn readings(&self) -> &[Reading] {
+        &self.readings
+    }
+
+    /// Frequency, governor and throttle counts per core.
+    pub fn freqs(&self) -> &[CoreFreq] {
+        &self.freqs
+    }
+
+    /// Pressure stall information, `None` on kernels without PSI.
+    pub fn psi(&self) -> Option<&Psi> {
+        self.psi.as_ref()
+    }
+
+    /// Batteries and whether mains power is connected.
+    pub fn power(&self) -> &PowerSupplies {
+        &self.power
+    }
+
+    /// RAPL power draw per zone over the last interval.
+    pub fn rapl(&self) -> &[PowerZone] {
+        &self.rapl
+    }
+
+    /// Memory and swap from /proc/meminfo.
+    pub fn memory(&self) -> Option<&MemInfo> {
+        self.mem.as_ref()
+    }
+
+    /// 1, 5 and 15 minute load averages.
+    pub fn load_average(&self) -> Option<(f32, f32, f32)> {
+        self.load_avg
+    }
+
+    pub fn uptime(&self) -> Option<Duration> {
+        self.uptime
+    }
+
+    pub fn boot_time(&self) -> Option<OffsetDateTime> {
+        self.boot_time
+    }
+
+    /// Hostname and kernel release.
+    pub fn host(&self) -> Option<(&str, &str)> {
+        self.host.as_ref().map(|(h, k)| (h.as_str(), k.as_str()))
+    }
+
+    /// Every mounted filesystem, sorted by mount point.
+    pub fn mounts(&self) -> Option<&[MountInfo]> {
+        self.mounts.as_deref()
+    }
+
+    /// Block device throughput since the previous sample.
+    pub fn disks(&self) -> Option<&[DiskIo]> {
+        self.disks.as_deref()
+    }
+
+    /// Network interfaces and their throughput since the previous sample.
+    pub fn network(&self) -> Option<&[Interface]> {
+        self.network.as_deref()
+    }
+
+    pub fn processes(&self) -> Option<&[Process]> {
+        self.processes.as_deref()
+    }
+
+    /// Why each failing collector failed, by collector name.
+    pub fn errors(&self) -> &BTreeMap<String, String> {
+        &self.errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn failure_keeps_the_last_good_sample() {
+        let mut loads = Loads::new();
+        let rapl = |result| Collected {
+            name: "rapl",
+            result,
+        };
+        let zone = PowerZone {
+            name: "package-0".to_owned(),
+            package: true,
+            watts: 12.5,
+        };
+        loads.apply(rapl(Ok(Sample::Rapl(vec![zone]))));
+        loads.apply(rapl(Err(io::Error::other("gone"))));
+        assert_eq!(loads.rapl().len(), 1);
+        assert_eq!(loads.errors().get("rapl").map(|e| e.as_str()), Some("gone"));
+
+        loads.apply(rapl(Ok(Sample::Rapl(Vec::new()))));
+        assert!(loads.errors().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_json() {
+        use bytesize::ByteSize;
+        let mut loads = Loads::new();
+        loads.apply(Collected {
+            name: "cpu",
+            result: Ok(Sample::Cpu {
+                total: CoreLoad {
+                    idle: 75.0,
+                    ..CoreLoad::default()
+                },
+                cores: Vec::new(),
+            }),
+        });
+        loads.apply(Collected {
+            name: "rapl",
+            result: Err(io::Error::other("gone")),
+        });
+        loads.apply(Collected {
+            name: "memory",
+            result: Ok(Sample::Memory(MemInfo {
+                total: ByteSize::gib(8),
+                available: ByteSize::gib(6),
+                swap_used: ByteSize::kib(4),
+                ..MemInfo::default()
+            })),
+        });
+        loads.apply(Collected {
+            name: "processes",
+            result: Ok(Sample::Processes(vec![Process {
+                pid: 42,
+                ppid: 1,
+                user: "root".to_owned(),
+                cpu: 12.5,
+                rss: ByteSize::mib(3),
+                state: 'S',
+                nice: -5,
+                name: "sshd".to_owned(),
+                command: "/usr/sbin/sshd -D".to_owned(),
+            }])),
+        });
+        loads.apply(Collected {
+            name: "host",
+            result: Ok(Sample::Host {
+                load_avg: None,
+                uptime: Some(Duration::from_secs(3600)),
+                boot_time: OffsetDateTime::from_unix_timestamp(1_700_000_000).ok(),
+                host: None,
+            }),
+        });
+        let json = serde_json::to_string(&loads).unwrap();
+        // numbers a script can use, not "8.6 GB" or {secs, nanos}
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["mem"]["total"], 8 * 1024 * 1024 * 1024u64);
+        assert_eq!(value["mem"]["swap_used"], 4096);
+        assert_eq!(value["processes"][0]["rss"], 3 * 1024 * 1024);
+        assert_eq!(value["uptime"], 3600);
+        assert_eq!(value["boot_time"], 1_700_000_000);
+
+        let back: Loads = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.cpu().map(|c| c.busy()), Some(25.0));
+        assert_eq!(back.errors().get("rapl").map(|e| e.as_str()), Some("gone"));
+        let mem = back.memory().unwrap();
+        assert_eq!(mem.total, ByteSize::gib(8));
+        assert_eq!(mem.used(), ByteSize::gib(2));
+        let process = &back.processes().unwrap()[0];
+        assert_eq!((process.pid, process.ppid, process.nice), (42, 1, -5));
+        assert_eq!(process.rss, ByteSize::mib(3));
+        assert_eq!(process.state, 'S');
+        assert_eq!(process.command, "/usr/sbin/sshd -D");
+        assert_eq!(back.uptime(), Some(Duration::from_secs(3600)));
+        assert_eq!(
+            back.boot_time().map(|t| t.unix_timestamp()),
+            Some(1_700_000_000)
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use app::App;
+use clap::Parser;
 use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -8,15 +9,42 @@ use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
-use std::sync::mpsc::sync_channel;
-use std::{error::Error, io, thread, time};
+use std::{error::Error, io, time::Duration};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
+#[allow(non_snake_case)]
 mod UI;
 mod app;
+mod batch;
+mod cli;
+mod config;
 mod events;
+mod history;
+#[allow(dead_code)]
 mod systemstat_example;
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let res = runtime.block_on(start());
+    // a collector stuck on a dead mount isnt worth waiting for on the way out
+    runtime.shutdown_background();
+    res
+}
+
+async fn start() -> Result<(), Box<dyn Error>> {
+    let args = cli::Args::parse();
+    let config = match config::load(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("sys_mon: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Some((count, format)) = args.batch() {
+        return batch::run(count, format, &config).await;
+    }
+
     //setup terminal
     enable_raw_mode()?;
     //use to log to stderr
@@ -28,48 +56,100 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     //create app and run it
-    let mut app = App::new();
+    let mut app = App::new(config);
 
-    let _res: Result<bool, io::Error> = run_app(&mut terminal, &mut app);
+    let res = run_app(&mut terminal, &mut app, &args).await;
 
     // clean up
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
+    res?;
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
-    // create channels
-    let (tx, rx) = sync_channel(2);
+async fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    args: &cli::Args,
+) -> io::Result<()> {
+    // cancelled on quit so the poller stops right away instead of finishing a tick
+    let cancel = CancellationToken::new();
+    let (tx, mut rx) = mpsc::channel(2);
     // spawn worker for system stats
-    let mut poller = app::Poller::new();
-    let _worker = thread::spawn(move || poller.sys_mon(tx));
-    //set reciever for system stats
-    app.set_reciever(rx);
+    let poller = sys_mon::Poller::new(sys_mon::Registry::with_defaults(), app.schedule_watch());
+    let worker = tokio::spawn(poller.run(tx, cancel.clone()));
 
-    // spawn event KeyPressHandler
-    let (tx, rx) = std::sync::mpsc::channel::<Option<events::KeyActions>>();
-    let mut kph = events::KeyPressHandler::new(tx);
-    app.set_event_handleer(rx);
-    let _event_handler = thread::spawn(move || kph.poll());
+    let mut events = events::KeyPressHandler::new(app.config().keys.clone());
+    // new samples are drawn on the next frame so a burst of them only draws once
+    let mut frames = frame_timer(app.render_rate());
+    // edits to the config file are picked up within a second
+    let mut watcher = config::path(args).map(config::Watcher::new);
+    let mut config_check = tokio::time::interval(Duration::from_secs(1));
+    let mut dirty = true;
 
     // Draw loop
-    loop {
-        match app.state {
-            app::State::Quit => break,
-            _ => {}
+    while let app::State::Run = app.state {
+        tokio::select! {
+            _ = frames.tick() => {
+                // keep drawing while a status message is up so it goes away on time
+                if dirty || app.get_status().is_some() {
+                    terminal.draw(|f| UI::ui(f, app))?;
+                    dirty = false;
+                }
+            }
+            Some(loads) = rx.recv() => {
+                app.update(loads);
+                dirty = true;
+            }
+            event = events.next(app.is_typing()) => match event {
+                // keys get drawn straight away
+                Some(events::Event::Key(key)) => {
+                    app.handle_key(key);
+                    // a picked chart style goes in the config file so it sticks
+                    let picked = app.take_graph();
+                    if let (false, Some(watcher)) = (picked.is_empty(), watcher.as_mut()) {
+                        for (chart, style) in picked {
+                            if let Err(e) = config::save_chart_style(watcher.path(), chart, style) {
+                                app.set_status(format!("couldnt save the chart style, {}", e), true);
+                            }
+                        }
+                        // our own write, nothing to reload
+                        watcher.changed();
+                    }
+                    terminal.draw(|f| UI::ui(f, app))?;
+                }
+                Some(events::Event::Redraw) => {
+                    terminal.draw(|f| UI::ui(f, app))?;
+                }
+                None => break,
+            },
+            _ = config_check.tick(), if watcher.is_some() => {
+                if watcher.as_mut().is_some_and(|w| w.changed()) {
+                    let config = config::load(args);
+                    if let Ok(config) = &config {
+                        events.set_keymap(config.keys.clone());
+                        if config.render_rate != app.render_rate() {
+                            frames = frame_timer(config.render_rate);
+                        }
+                    }
+                    app.reload_config(config);
+                    dirty = true;
+                }
+            }
+            else => break,
         }
-        app.poll();
-        if app.check_keys().is_err() {
-            break;
-        }
-        //render terminal
-        terminal.draw(|f| UI::ui(f, app))?;
-        thread::sleep(time::Duration::from_millis(300));
     }
 
-    // if worker.join().is_ok(){}
-    Ok(true)
+    cancel.cancel();
+    let _ = worker.await;
+    Ok(())
+}
+
+// ticks for drawing, frames that were missed are skipped rather than caught up
+fn frame_timer(rate: Duration) -> tokio::time::Interval {
+    let mut frames = tokio::time::interval(rate);
+    frames.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    frames
 }
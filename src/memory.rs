@@ -0,0 +1,153 @@
+/*
+- The /proc/meminfo picture. Page cache and buffers get handed back as soon as
+- something needs them so "used" here is total - available like free(1)
+*/
+use crate::collector::{Collector, Sample};
+use bytesize::ByteSize;
+use std::collections::BTreeMap;
+use std::io;
+use systemstat::{saturating_sub_bytes, Platform, System};
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct MemInfo {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub total: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub free: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub available: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub buffers: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub cached: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub shared: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub slab: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub dirty: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub swap_total: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub swap_used: ByteSize,
+}
+
+impl MemInfo {
+    fn from_meminfo(meminfo: &BTreeMap<String, ByteSize>) -> Self {
+        let get = |key: &str| meminfo.get(key).copied().unwrap_or_default();
+        MemInfo {
+            total: get("MemTotal"),
+            free: get("MemFree"),
+            available: get("MemAvailable"),
+            buffers: get("Buffers"),
+            // reclaimable slab is cache in all but name, same as free(1)
+            cached: get("Cached") + get("SReclaimable"),
+            shared: get("Shmem"),
+            slab: get("Slab"),
+            dirty: get("Dirty"),
+            swap_total: get("SwapTotal"),
+            swap_used: saturating_sub_bytes(get("SwapTotal"), get("SwapFree")),
+        }
+    }
+
+    // what applications are actually holding on to
+    pub fn used(&self) -> ByteSize {
+        saturating_sub_bytes(self.total, self.available)
+    }
+
+    // share of total memory, used for the gauges
+    pub fn percent(&self, part: ByteSize) -> f64 {
+        if self.total.as_u64() == 0 {
+            0.0
+        } else {
+            part.as_u64() as f64 / self.total.as_u64() as f64 * 100.0
+        }
+    }
+
+    pub fn swap_percent(&self) -> f64 {
+        if self.swap_total.as_u64() == 0 {
+            0.0
+        } else {
+            self.swap_used.as_u64() as f64 / self.swap_total.as_u64() as f64 * 100.0
+        }
+    }
+}
+
+pub struct MemoryCollector;
+
+impl Collector for MemoryCollector {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn collect(&mut self, sys: &System) -> io::Result<Sample> {
+        read_memory(sys).map(Sample::Memory)
+    }
+}
+
+pub fn read_memory(sys: &System) -> io::Result<MemInfo> {
+    let mem = sys.memory()?;
+    Ok(MemInfo::from_meminfo(&mem.platform_memory.meminfo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemInfo;
+    use bytesize::ByteSize;
+    use std::collections::BTreeMap;
+
+    // a trimmed down /proc/meminfo, parsed into the map systemstat hands us
+    const MEMINFO: &str = "MemTotal:       16303364 kB
+MemFree:         1032884 kB
+MemAvailable:    9486104 kB
+Buffers:          412036 kB
+Cached:          7702100 kB
+SwapCached:        12540 kB
+Active:          8036220 kB
+Inactive:        5349204 kB
+Dirty:              1108 kB
+Shmem:            652424 kB
+Slab:             802744 kB
+SReclaimable:     513572 kB
+SwapTotal:       2097148 kB
+SwapFree:        1572860 kB
+HugePages_Total:       0
+";
+
+    fn parse(text: &str) -> BTreeMap<String, ByteSize> {
+        text.lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                let kb: u64 = value.trim().trim_end_matches(" kB").parse().ok()?;
+                Some((key.to_owned(), ByteSize::kib(kb)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn breaks_down_meminfo() {
+        let mem = MemInfo::from_meminfo(&parse(MEMINFO));
+        assert_eq!(mem.total, ByteSize::kib(16303364));
+        assert_eq!(mem.available, ByteSize::kib(9486104));
+        // reclaimable slab counts as cache
+        assert_eq!(mem.cached, ByteSize::kib(7702100 + 513572));
+        assert_eq!(mem.shared, ByteSize::kib(652424));
+        assert_eq!(mem.used(), ByteSize::kib(16303364 - 9486104));
+        assert_eq!(mem.swap_used, ByteSize::kib(2097148 - 1572860));
+        assert!((mem.swap_percent() - 25.0).abs() < 0.01);
+        assert!((mem.percent(mem.used()) - 41.8).abs() < 0.1);
+    }
+
+    #[test]
+    fn missing_fields_are_zero() {
+        let mem = MemInfo::from_meminfo(&parse("MemTotal: 1024 kB\n"));
+        assert_eq!(mem.available, ByteSize::b(0));
+        assert_eq!(mem.swap_percent(), 0.0);
+        assert_eq!(MemInfo::default().percent(ByteSize::kib(1)), 0.0);
+    }
+}
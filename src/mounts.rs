@@ -0,0 +1,163 @@
+/*
+- Mounted filesystems for the mounts panel
+*/
+use crate::collector::{Collector, Sample};
+use bytesize::ByteSize;
+use std::io;
+use std::time::Duration;
+use systemstat::{saturating_sub_bytes, Filesystem, Platform, System};
+
+// Filesystems that dont live on a disk. Hidden unless the user asks for them
+const PSEUDO_FS: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "overlay",
+    "proc",
+    "pstore",
+    "ramfs",
+    "securityfs",
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+];
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct MountInfo {
+    pub device: String,
+    pub fs_type: String,
+    pub mount_point: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub total: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub used: ByteSize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub avail: ByteSize,
+    pub inodes_total: usize,
+    pub inodes_used: usize,
+    pub pseudo: bool,
+}
+
+impl MountInfo {
+    // percent of the space a normal user can get at, same as df
+    pub fn percent(&self) -> f64 {
+        let usable = self.used.as_u64() + self.avail.as_u64();
+        if usable == 0 {
+            0.0
+        } else {
+            self.used.as_u64() as f64 / usable as f64 * 100.0
+        }
+    }
+
+    pub fn inode_percent(&self) -> f64 {
+        if self.inodes_total == 0 {
+            0.0
+        } else {
+            self.inodes_used as f64 / self.inodes_total as f64 * 100.0
+        }
+    }
+}
+
+impl From<&Filesystem> for MountInfo {
+    fn from(fs: &Filesystem) -> Self {
+        MountInfo {
+            device: fs.fs_mounted_from.clone(),
+            fs_type: fs.fs_type.clone(),
+            mount_point: fs.fs_mounted_on.clone(),
+            total: fs.total,
+            used: saturating_sub_bytes(fs.total, fs.free),
+            avail: fs.avail,
+            inodes_total: fs.files_total,
+            inodes_used: fs.files_total.saturating_sub(fs.files_avail),
+            pseudo: is_pseudo(&fs.fs_type),
+        }
+    }
+}
+
+pub fn is_pseudo(fs_type: &str) -> bool {
+    PSEUDO_FS.contains(&fs_type)
+}
+
+pub struct MountCollector;
+
+impl Collector for MountCollector {
+    fn name(&self) -> &'static str {
+        "mounts"
+    }
+
+    // statvfs on every mount is slow on network filesystems and usage barely moves
+    fn interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(5))
+    }
+
+    fn collect(&mut self, sys: &System) -> io::Result<Sample> {
+        read_mounts(sys).map(Sample::Mounts)
+    }
+}
+
+// Every mount sorted by mount point so the table doesnt jump around between ticks
+pub fn read_mounts(sys: &System) -> io::Result<Vec<MountInfo>> {
+    let mut mounts: Vec<MountInfo> = sys.mounts()?.iter().map(MountInfo::from).collect();
+    mounts.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    Ok(mounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mount(fs_type: &str, total: u64, free: u64, avail: u64) -> MountInfo {
+        MountInfo::from(&Filesystem {
+            files: 0,
+            files_total: 1000,
+            files_avail: 250,
+            free: ByteSize::b(free),
+            avail: ByteSize::b(avail),
+            total: ByteSize::b(total),
+            name_max: 255,
+            fs_type: fs_type.to_owned(),
+            fs_mounted_from: "/dev/sda1".to_owned(),
+            fs_mounted_on: "/".to_owned(),
+        })
+    }
+
+    #[test]
+    fn only_disk_backed_filesystems_are_real() {
+        for fs_type in ["proc", "sysfs", "tmpfs", "overlay", "cgroup2"] {
+            assert!(mount(fs_type, 100, 100, 100).pseudo, "{}", fs_type);
+        }
+        for fs_type in ["ext4", "btrfs", "xfs", "vfat"] {
+            assert!(!mount(fs_type, 100, 50, 50).pseudo, "{}", fs_type);
+        }
+    }
+
+    #[test]
+    fn used_percent_leaves_out_the_root_reserve() {
+        // 100 GB with 30 free of which 25 are usable, df says 70 / 95
+        let gb = 1_000_000_000;
+        let disk = mount("ext4", 100 * gb, 30 * gb, 25 * gb);
+        assert_eq!(disk.used, ByteSize::b(70 * gb));
+        assert!((disk.percent() - 70.0 / 95.0 * 100.0).abs() < 1e-9);
+        assert_eq!(disk.inode_percent(), 75.0);
+
+        // procfs and friends report no blocks at all
+        let empty = mount("proc", 0, 0, 0);
+        assert_eq!(empty.used, ByteSize::b(0));
+        assert_eq!(empty.percent(), 0.0);
+    }
+}
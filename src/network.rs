@@ -0,0 +1,199 @@
+/*
+- Network interfaces, their addresses and throughput between ticks
+*/
+use crate::collector::{Collector, Sample};
+use crate::sysfs::read_number;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+use systemstat::{IpAddr, Network, Platform, System};
+
+// raw counters for one interface, these only ever go up
+#[derive(Clone, Copy, Default)]
+struct Counters {
+    rx_bytes: u64,
+    tx_bytes: u64,
+    rx_packets: u64,
+    tx_packets: u64,
+    rx_errors: u64,
+    tx_errors: u64,
+    rx_dropped: u64,
+    tx_dropped: u64,
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct Interface {
+    pub name: String,
+    // "addr/prefix" for every v4 and v6 address
+    pub addrs: Vec<String>,
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+    pub rx_pps: f64,
+    pub tx_pps: f64,
+    // errors and drops are totals since the interface came up
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+}
+
+impl Interface {
+    // rates from two readings `secs` apart, 0 without an earlier one. A counter
+    // that went backwards was reset and counts as 0 too
+    fn from_counters(
+        name: String,
+        addrs: Vec<String>,
+        counters: Counters,
+        prev: Option<Counters>,
+        secs: Option<f64>,
+    ) -> Self {
+        let per_sec = |pick: fn(&Counters) -> u64| match (secs, prev) {
+            (Some(secs), Some(prev)) => pick(&counters).saturating_sub(pick(&prev)) as f64 / secs,
+            _ => 0.0,
+        };
+        Interface {
+            name,
+            addrs,
+            rx_bps: per_sec(|c| c.rx_bytes),
+            tx_bps: per_sec(|c| c.tx_bytes),
+            rx_pps: per_sec(|c| c.rx_packets),
+            tx_pps: per_sec(|c| c.tx_packets),
+            rx_errors: counters.rx_errors,
+            tx_errors: counters.tx_errors,
+            rx_dropped: counters.rx_dropped,
+            tx_dropped: counters.tx_dropped,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct NetSampler {
+    prev: HashMap<String, Counters>,
+    taken: Option<Instant>,
+}
+
+impl NetSampler {
+    // Every interface with rates since the last call, rates are 0 on the first one
+    pub fn sample(&mut self, sys: &System) -> io::Result<Vec<Interface>> {
+        let networks = sys.networks()?;
+        let now = Instant::now();
+        let secs = self
+            .taken
+            .map(|t| now.duration_since(t).as_secs_f64().max(0.001));
+
+        let mut interfaces = Vec::new();
+        let mut seen = HashMap::new();
+        for network in networks.values() {
+            let counters = match read_counters(sys, &network.name) {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+            let prev = self.prev.get(&network.name).copied();
+            interfaces.push(Interface::from_counters(
+                network.name.clone(),
+                addresses(network),
+                counters,
+                prev,
+                secs,
+            ));
+            seen.insert(network.name.clone(), counters);
+        }
+
+        self.prev = seen;
+        self.taken = Some(now);
+        Ok(interfaces)
+    }
+}
+
+impl Collector for NetSampler {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn collect(&mut self, sys: &System) -> io::Result<Sample> {
+        self.sample(sys).map(Sample::Network)
+    }
+}
+
+fn read_counters(sys: &System, name: &str) -> io::Result<Counters> {
+    let stats = sys.network_stats(name)?;
+    // systemstat doesnt expose drops so grab them from sysfs ourselves
+    let dropped = |file: &str| {
+        let path = format!("/sys/class/net/{}/statistics/{}", name, file);
+        read_number(Path::new(&path)).unwrap_or(0)
+    };
+    Ok(Counters {
+        rx_bytes: stats.rx_bytes.as_u64(),
+        tx_bytes: stats.tx_bytes.as_u64(),
+        rx_packets: stats.rx_packets,
+        tx_packets: stats.tx_packets,
+        rx_errors: stats.rx_errors,
+        tx_errors: stats.tx_errors,
+        rx_dropped: dropped("rx_dropped"),
+        tx_dropped: dropped("tx_dropped"),
+    })
+}
+
+fn addresses(network: &Network) -> Vec<String> {
+    network
+        .addrs
+        .iter()
+        .filter_map(|a| match (&a.addr, &a.netmask) {
+            (IpAddr::V4(addr), IpAddr::V4(mask)) => {
+                Some(format!("{}/{}", addr, u32::from(*mask).count_ones()))
+            }
+            (IpAddr::V6(addr), IpAddr::V6(mask)) => {
+                Some(format!("{}/{}", addr, u128::from(*mask).count_ones()))
+            }
+            (IpAddr::V4(addr), _) => Some(addr.to_string()),
+            (IpAddr::V6(addr), _) => Some(addr.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Counters, Interface};
+
+    #[test]
+    fn rates_between_two_readings() {
+        let prev = Counters {
+            rx_bytes: 1_000,
+            tx_bytes: 5_000,
+            rx_packets: 10,
+            tx_packets: 20,
+            ..Counters::default()
+        };
+        let now = Counters {
+            rx_bytes: 3_000,
+            // the interface was reset in between
+            tx_bytes: 100,
+            rx_packets: 30,
+            tx_packets: 20,
+            rx_errors: 2,
+            tx_dropped: 7,
+            ..Counters::default()
+        };
+        let interface =
+            |prev, secs| Interface::from_counters("eth0".to_owned(), Vec::new(), now, prev, secs);
+
+        let eth = interface(Some(prev), Some(2.0));
+        assert_eq!(eth.rx_bps, 1_000.0);
+        assert_eq!(eth.tx_bps, 0.0);
+        assert_eq!(eth.rx_pps, 10.0);
+        assert_eq!(eth.tx_pps, 0.0);
+        // errors and drops are passed through as totals
+        assert_eq!(eth.rx_errors, 2);
+        assert_eq!(eth.tx_dropped, 7);
+
+        // nothing to diff against on the first reading
+        assert_eq!(interface(None, Some(2.0)).rx_bps, 0.0);
+        assert_eq!(interface(Some(prev), None).rx_bps, 0.0);
+    }
+}
@@ -0,0 +1,180 @@
+/*
+- Drives the collectors. Poller runs them on a timer for the TUI or anyone else
+  with a tokio runtime, snapshot() samples once and hands back the result
+*/
+use crate::collector::{Collected, Registry};
+use crate::loads::Loads;
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::Duration;
+use systemstat::{Platform, System};
+use tokio::sync::{mpsc, watch};
+use tokio::time::{interval_at, Interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+
+/// How long [`snapshot`] measures for. Cpu load and every rate are averages
+/// over this window.
+pub const SNAPSHOT_WINDOW: Duration = Duration::from_millis(500);
+
+/// When the [`Poller`] runs its collectors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schedule {
+    /// Time between samples.
+    pub interval: Duration,
+    /// How often a collector runs, by name, in place of the interval it asks for.
+    pub collectors: BTreeMap<String, Duration>,
+}
+
+impl From<Duration> for Schedule {
+    fn from(interval: Duration) -> Self {
+        Schedule {
+            interval,
+            collectors: BTreeMap::new(),
+        }
+    }
+}
+
+/// Runs a [`Registry`] of collectors every sample interval.
+pub struct Poller {
+    collectors: Registry,
+    // the app sends a new schedule here when '+' or '-' is pressed or the config changes
+    schedule: watch::Receiver<Schedule>,
+}
+
+// a ticker that doesnt try to catch up when a sample runs long
+fn ticker(period: Duration) -> Interval {
+    let mut ticker = interval_at(tokio::time::Instant::now() + period, period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+impl Poller {
+    /// The schedule can be changed while running by sending on the watch.
+    pub fn new(collectors: Registry, schedule: watch::Receiver<Schedule>) -> Self {
+        Poller {
+            collectors,
+            schedule,
+        }
+    }
+
+    /// Send whatever collectors are due every interval, until the token is
+    /// cancelled or the receiver is dropped. Feed each batch to [`Loads::apply`].
+    /// Collection runs on the blocking pool and cancelling doesnt wait for it,
+    /// so a runtime that has to exit promptly should be shut down in the background.
+    pub async fn run(self, tx: mpsc::Sender<Vec<Collected>>, cancel: CancellationToken) {
+        let Poller {
+            mut collectors,
+            schedule: mut watch,
+        } = self;
+        let mut sys = System::new();
+        let mut schedule = watch.borrow_and_update().clone();
+        collectors.set_intervals(&schedule.collectors);
+        let mut ticks = ticker(schedule.interval);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticks.tick() => {}
+                Ok(()) = watch.changed() => {
+                    let next = watch.borrow_and_update().clone();
+                    collectors.set_intervals(&next.collectors);
+                    // a new interval counts from now, the cpu window carries on
+                    if next.interval != schedule.interval {
+                        ticks = ticker(next.interval);
+                    }
+                    schedule = next;
+                    continue;
+                }
+            }
+            // walking /proc and statvfs on a dead network mount can take a
+            // long time, none of it gets to hold up a cancel
+            let job = tokio::task::spawn_blocking(move || {
+                let batch = collectors.collect_due(&sys);
+                (collectors, sys, batch)
+            });
+            let batch = tokio::select! {
+                _ = cancel.cancelled() => break,
+                done = job => match done {
+                    Ok((back, s, batch)) => {
+                        collectors = back;
+                        sys = s;
+                        batch
+                    }
+                    Err(_) => break,
+                },
+            };
+            // Send results
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                sent = tx.send(batch) => if sent.is_err() {
+                    break;
+                },
+            }
+        }
+    }
+}
+
+/// Sample everything once over [`SNAPSHOT_WINDOW`]. Blocks for that long.
+pub fn snapshot() -> Loads {
+    snapshot_over(SNAPSHOT_WINDOW)
+}
+
+/// Sample everything once, with rates and cpu load averaged over `window`.
+pub fn snapshot_over(window: Duration) -> Loads {
+    snapshot_with(&mut Registry::with_defaults(), &System::new(), window)
+}
+
+/// Sample every collector in `collectors` once over `window`. They are left
+/// primed, so [`Registry::collect_due`] can carry on from here.
+pub fn snapshot_with(collectors: &mut Registry, sys: &System, window: Duration) -> Loads {
+    let mut loads = Loads::new();
+    // the first pass only primes the counters the rates are worked out from
+    collectors.collect_due(sys);
+    thread::sleep(window);
+    // collectors on a slower interval than the window would be skipped otherwise
+    for collected in collectors.collect_all(sys) {
+        loads.apply(collected);
+    }
+    loads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_has_every_collector() {
+        let mut collectors = Registry::with_defaults();
+        let names = collectors.names();
+        let sys = System::new();
+        collectors.collect_due(&sys);
+        let batch = collectors.collect_all(&sys);
+        assert_eq!(batch.iter().map(|c| c.name).collect::<Vec<_>>(), names);
+
+        // mounts only want to run every few seconds
+        let loads = snapshot_over(Duration::from_millis(10));
+        assert!(loads.mounts().is_some() || loads.errors().contains_key("mounts"));
+        assert!(loads.processes().is_some() || loads.errors().contains_key("processes"));
+    }
+
+    #[tokio::test]
+    async fn picks_up_a_new_schedule_and_stops_on_cancel() {
+        let (schedule, watch) = watch::channel(Schedule::from(Duration::from_secs(3600)));
+        let (tx, mut rx) = mpsc::channel(1);
+        let cancel = CancellationToken::new();
+        let poller = tokio::spawn(Poller::new(Registry::default(), watch).run(tx, cancel.clone()));
+
+        // nothing for an hour, then a shorter interval arrives over the watch
+        let wait = Duration::from_secs(5);
+        assert!(tokio::time::timeout(Duration::from_millis(50), rx.recv())
+            .await
+            .is_err());
+        schedule.send_replace(Schedule::from(Duration::from_millis(10)));
+        assert!(tokio::time::timeout(wait, rx.recv())
+            .await
+            .unwrap()
+            .is_some());
+
+        cancel.cancel();
+        tokio::time::timeout(wait, poller).await.unwrap().unwrap();
+    }
+}
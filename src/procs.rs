@@ -0,0 +1,253 @@
+/*
+- Process list straight out of /proc. CPU% is worked out from the change in
+- utime + stime between two ticks like top does
+*/
+use crate::collector::{Collector, Sample};
+use bytesize::ByteSize;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CStr;
+use std::fs;
+use std::io;
+use std::time::Instant;
+use systemstat::System;
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct Process {
+    pub pid: u32,
+    pub ppid: u32,
+    pub user: String,
+    // percent of one core, so a busy multithreaded process can go past 100
+    pub cpu: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_numbers::bytes"))]
+    pub rss: ByteSize,
+    pub state: char,
+    pub nice: i32,
+    pub name: String,
+    pub command: String,
+}
+
+// the bits of /proc/[pid]/stat we care about
+#[derive(Debug, PartialEq)]
+struct Stat {
+    name: String,
+    state: char,
+    ppid: u32,
+    // utime + stime in clock ticks
+    cpu_ticks: u64,
+    nice: i32,
+}
+
+pub struct ProcSampler {
+    prev: HashMap<u32, u64>,
+    taken: Option<Instant>,
+    users: HashMap<u32, String>,
+    clock_ticks: f64,
+}
+
+impl Default for ProcSampler {
+    fn default() -> Self {
+        ProcSampler {
+            prev: HashMap::new(),
+            taken: None,
+            users: HashMap::new(),
+            clock_ticks: match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+                t if t > 0 => t as f64,
+                _ => 100.0,
+            },
+        }
+    }
+}
+
+impl ProcSampler {
+    // Every process we can read. Processes that exit while we are looking are skipped
+    pub fn sample(&mut self) -> io::Result<Vec<Process>> {
+        let now = Instant::now();
+        let secs = self
+            .taken
+            .map(|t| now.duration_since(t).as_secs_f64().max(0.001));
+
+        let mut processes = Vec::new();
+        let mut seen = HashMap::new();
+        for entry in fs::read_dir("/proc")? {
+            let pid = match entry
+                .ok()
+                .and_then(|e| e.file_name().to_str()?.parse().ok())
+            {
+                Some(pid) => pid,
+                None => continue,
+            };
+            let stat = match fs::read_to_string(format!("/proc/{}/stat", pid))
+                .ok()
+                .and_then(|s| parse_stat(&s))
+            {
+                Some(stat) => stat,
+                None => continue,
+            };
+            let status = fs::read_to_string(format!("/proc/{}/status", pid)).unwrap_or_default();
+            let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).unwrap_or_default();
+
+            let cpu = match (secs, self.prev.get(&pid)) {
+                (Some(secs), Some(prev)) => {
+                    stat.cpu_ticks.saturating_sub(*prev) as f64 / self.clock_ticks / secs * 100.0
+                }
+                _ => 0.0,
+            };
+            seen.insert(pid, stat.cpu_ticks);
+
+            let uid = status_field(&status, "Uid:").and_then(|v| v.parse().ok());
+            // kernel threads have no cmdline, show the name in brackets like ps
+            let command = if cmdline.is_empty() {
+                format!("[{}]", stat.name)
+            } else {
+                String::from_utf8_lossy(&cmdline)
+                    .trim_end_matches('\0')
+                    .replace('\0', " ")
+            };
+
+            processes.push(Process {
+                pid,
+                ppid: stat.ppid,
+                user: match uid {
+                    Some(uid) => self.user_name(uid),
+                    None => "?".to_owned(),
+                },
+                cpu: cpu as f32,
+                rss: status_field(&status, "VmRSS:")
+                    .and_then(|v| v.parse().ok())
+                    .map(ByteSize::kib)
+                    .unwrap_or_default(),
+                state: stat.state,
+                nice: stat.nice,
+                name: stat.name,
+                command,
+            });
+        }
+
+        self.prev = seen;
+        self.taken = Some(now);
+        Ok(processes)
+    }
+
+    // uid -> login name, cached since /etc/passwd doesnt change much
+    fn user_name(&mut self, uid: u32) -> String {
+        self.users
+            .entry(uid)
+            .or_insert_with(|| lookup_user(uid).unwrap_or_else(|| uid.to_string()))
+            .clone()
+    }
+}
+
+impl Collector for ProcSampler {
+    fn name(&self) -> &'static str {
+        "processes"
+    }
+
+    fn collect(&mut self, _sys: &System) -> io::Result<Sample> {
+        self.sample().map(Sample::Processes)
+    }
+}
+
+fn lookup_user(uid: u32) -> Option<String> {
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut result = std::ptr::null_mut();
+    let ret = unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if ret != 0 || result.is_null() {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr(pwd.pw_name) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+// first value of a "Key:\tvalue ..." line in /proc/[pid]/status
+fn status_field<'a>(status: &'a str, key: &str) -> Option<&'a str> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(key))
+        .and_then(|rest| rest.split_whitespace().next())
+}
+
+// The name is in parens and can have spaces or parens of its own, so split on
+// the last ')' and count fields from there
+fn parse_stat(stat: &str) -> Option<Stat> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_owned();
+    let fields: Vec<&str> = stat.get(close + 1..)?.split_whitespace().collect();
+
+    Some(Stat {
+        name,
+        state: fields.first()?.chars().next()?,
+        ppid: fields.get(1)?.parse().ok()?,
+        cpu_ticks: fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?,
+        nice: fields.get(16)?.parse().ok()?,
+    })
+}
+
+pub fn send_signal(pid: u32, signal: libc::c_int) -> io::Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+pub fn renice(pid: u32, nice: i32) -> io::Result<()> {
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+// Depth first walk from every process whose parent we cant see, children in
+// the order they already come in. Returns (depth, index into processes)
+pub fn tree_order(processes: &[&Process]) -> Vec<(usize, usize)> {
+    let pids: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+    let mut children: HashMap<u32, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (i, p) in processes.iter().enumerate() {
+        if p.ppid != p.pid && pids.contains(&p.ppid) {
+            children.entry(p.ppid).or_default().push(i);
+        } else {
+            roots.push(i);
+        }
+    }
+
+    let mut order = Vec::with_capacity(processes.len());
+    let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (0, i)).collect();
+    while let Some((depth, i)) = stack.pop() {
+        order.push((depth, i));
+        if let Some(kids) = children.get(&processes[i].pid) {
+            stack.extend(kids.iter().rev().map(|k| (depth + 1, *k)));
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_stat, Stat};
+
+    #[test]
+    fn stat_with_awkward_name() {
+        let line = "1234 (tmux: server (1)) S 1 1234 1234 0 -1 4194560 1 0 0 0 150 25 0 0 20 0 1 0";
+        assert_eq!(
+            parse_stat(line),
+            Some(Stat {
+                name: "tmux: server (1)".to_owned(),
+                state: 'S',
+                ppid: 1,
+                cpu_ticks: 175,
+                nice: 0,
+            })
+        );
+    }
+}
@@ -0,0 +1,134 @@
+/*
+- Pressure stall information from /proc/pressure. Only there on 4.20+ kernels
+- built with CONFIG_PSI, so callers should expect this to fail
+*/
+use crate::collector::{Collector, Sample};
+use std::fs;
+use std::io;
+use systemstat::System;
+
+// one "some" or "full" line, averages are percent of wall time stalled
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct PsiLine {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    // total stall time in microseconds
+    pub total: u64,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct Pressure {
+    // at least one task stalled
+    pub some: PsiLine,
+    // every non idle task stalled at once. Older kernels leave this out for cpu
+    pub full: Option<PsiLine>,
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct Psi {
+    pub cpu: Pressure,
+    pub memory: Pressure,
+    pub io: Pressure,
+}
+
+pub struct PsiCollector;
+
+impl Collector for PsiCollector {
+    fn name(&self) -> &'static str {
+        "psi"
+    }
+
+    fn collect(&mut self, _sys: &System) -> io::Result<Sample> {
+        read_psi().map(Sample::Psi)
+    }
+}
+
+pub fn read_psi() -> io::Result<Psi> {
+    let read = |resource: &str| -> io::Result<Pressure> {
+        let data = fs::read_to_string(format!("/proc/pressure/{}", resource))?;
+        parse_pressure(&data).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("could not parse /proc/pressure/{}", resource),
+            )
+        })
+    };
+    Ok(Psi {
+        cpu: read("cpu")?,
+        memory: read("memory")?,
+        io: read("io")?,
+    })
+}
+
+fn parse_line(rest: &str) -> Option<PsiLine> {
+    let mut line = PsiLine::default();
+    for field in rest.split_whitespace() {
+        let (key, value) = field.split_once('=')?;
+        match key {
+            "avg10" => line.avg10 = value.parse().ok()?,
+            "avg60" => line.avg60 = value.parse().ok()?,
+            "avg300" => line.avg300 = value.parse().ok()?,
+            "total" => line.total = value.parse().ok()?,
+            _ => {}
+        }
+    }
+    Some(line)
+}
+
+fn parse_pressure(data: &str) -> Option<Pressure> {
+    let mut some = None;
+    let mut full = None;
+    for line in data.lines() {
+        if let Some(rest) = line.strip_prefix("some ") {
+            some = parse_line(rest);
+        } else if let Some(rest) = line.strip_prefix("full ") {
+            full = parse_line(rest);
+        }
+    }
+    Some(Pressure { some: some?, full })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pressure, PsiLine};
+
+    #[test]
+    fn some_and_full() {
+        let data = "some avg10=5.84 avg60=3.81 avg300=2.98 total=40408431\n\
+                    full avg10=0.00 avg60=0.01 avg300=0.00 total=1200\n";
+        let pressure = parse_pressure(data).unwrap();
+        assert_eq!(pressure.some.avg10, 5.84);
+        assert_eq!(pressure.some.total, 40408431);
+        assert_eq!(
+            pressure.full,
+            Some(PsiLine {
+                avg10: 0.0,
+                avg60: 0.01,
+                avg300: 0.0,
+                total: 1200,
+            })
+        );
+    }
+
+    #[test]
+    fn old_kernel_cpu_has_no_full() {
+        let pressure = parse_pressure("some avg10=1.00 avg60=0.50 avg300=0.10 total=99\n").unwrap();
+        assert_eq!(pressure.full, None);
+        assert!(parse_pressure("").is_none());
+    }
+}
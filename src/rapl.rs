@@ -0,0 +1,200 @@
+/*
+- Power draw from the Intel RAPL powercap counters. Like the disks these are
+  energy counters that only go up, so we diff them between ticks. They wrap at
+  max_energy_range_uj and newer kernels make them root only
+*/
+use crate::collector::{Collector, Sample};
+use crate::sysfs::{read_number, read_text};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+use systemstat::System;
+
+// the app looks this up to say why the power panel is empty
+pub const NAME: &str = "rapl";
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct PowerZone {
+    // "package-0", "package-0 core", "package-0 dram" and so on
+    pub name: String,
+    // only the package-N zones add up to the total. Subzones are already
+    // counted in their package, and psys on laptops covers the packages too
+    pub package: bool,
+    pub watts: f64,
+}
+
+struct Counter {
+    energy_uj: u64,
+    max_uj: u64,
+}
+
+#[derive(Default)]
+pub struct RaplSampler {
+    prev: BTreeMap<String, Counter>,
+    taken: Option<Instant>,
+}
+
+impl RaplSampler {
+    // Watts since the last call, the first call only primes the counters
+    pub fn sample(&mut self) -> io::Result<Vec<PowerZone>> {
+        self.sample_at(Path::new("/sys/class/powercap"))
+    }
+
+    fn sample_at(&mut self, root: &Path) -> io::Result<Vec<PowerZone>> {
+        let counters = read_counters(root)?;
+        let now = Instant::now();
+
+        let mut zones = Vec::new();
+        if let Some(taken) = self.taken {
+            let secs = now.duration_since(taken).as_secs_f64().max(0.001);
+            for (name, counter) in &counters {
+                if let Some(prev) = self.prev.get(name) {
+                    zones.push(PowerZone {
+                        name: name.clone(),
+                        package: is_package(name),
+                        watts: energy_delta(prev.energy_uj, counter) as f64 / 1e6 / secs,
+                    });
+                }
+            }
+        }
+
+        self.prev = counters;
+        self.taken = Some(now);
+        Ok(zones)
+    }
+}
+
+fn is_package(name: &str) -> bool {
+    name.starts_with("package-") && !name.contains(' ')
+}
+
+impl Collector for RaplSampler {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn collect(&mut self, _sys: &System) -> io::Result<Sample> {
+        self.sample().map(Sample::Rapl)
+    }
+}
+
+// the counter starts over from zero once it passes the range
+fn energy_delta(prev: u64, now: &Counter) -> u64 {
+    if now.energy_uj >= prev {
+        now.energy_uj - prev
+    } else {
+        now.max_uj.saturating_sub(prev) + now.energy_uj
+    }
+}
+
+// zones keyed by a readable name. Subzones like intel-rapl:0:1 get their
+// package name in front so "dram" from two sockets doesnt collide
+fn read_counters(root: &Path) -> io::Result<BTreeMap<String, Counter>> {
+    let mut dirs: Vec<_> = fs::read_dir(root)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                io::Error::new(e.kind(), "no powercap interface, not an Intel system?")
+            }
+            _ => e,
+        })?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with("intel-rapl:"))
+        .collect();
+    dirs.sort();
+    if dirs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no intel-rapl zones, is the intel_rapl driver loaded?",
+        ));
+    }
+
+    let mut counters = BTreeMap::new();
+    let mut denied = None;
+    for dir in &dirs {
+        let path = root.join(dir);
+        let energy = match fs::read_to_string(path.join("energy_uj")) {
+            Ok(text) => text,
+            Err(e) => {
+                denied = Some(e);
+                continue;
+            }
+        };
+        let (Some(energy_uj), Some(name)) =
+            (energy.trim().parse().ok(), read_text(&path.join("name")))
+        else {
+            continue;
+        };
+        let max_uj = read_number(&path.join("max_energy_range_uj")).unwrap_or(u64::MAX);
+        let name = match dir.rsplit_once(':') {
+            // intel-rapl:0:1 belongs to intel-rapl:0
+            Some((parent, _)) if parent.contains(':') => {
+                let package =
+                    read_text(&root.join(parent).join("name")).unwrap_or_else(|| parent.to_owned());
+                format!("{} {}", package, name)
+            }
+            _ => name,
+        };
+        counters.insert(name, Counter { energy_uj, max_uj });
+    }
+
+    // every zone failing is worth telling the user about, usually permissions
+    match denied {
+        Some(e) if counters.is_empty() => Err(match e.kind() {
+            io::ErrorKind::PermissionDenied => io::Error::new(
+                e.kind(),
+                "energy_uj is only readable by root on this kernel",
+            ),
+            _ => e,
+        }),
+        _ => Ok(counters),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn turns_energy_into_watts_across_a_wrap() {
+        let root = TestDir::new("rapl");
+        let zones = [
+            ("intel-rapl:0", "package-0"),
+            ("intel-rapl:0:2", "dram"),
+            ("intel-rapl:1", "psys"),
+        ];
+        for (dir, name) in zones {
+            root.write(&format!("{}/name", dir), name);
+            root.write(&format!("{}/max_energy_range_uj", dir), "1000000000");
+        }
+        root.write("intel-rapl:0/energy_uj", "999000000");
+        root.write("intel-rapl:0:2/energy_uj", "5000000");
+        root.write("intel-rapl:1/energy_uj", "1000000");
+
+        let mut sampler = RaplSampler::default();
+        assert!(sampler.sample_at(&root).unwrap().is_empty());
+        // pretend a whole second went by so the numbers come out even
+        sampler.taken = Some(Instant::now() - std::time::Duration::from_secs(1));
+        root.write("intel-rapl:0/energy_uj", "9000000");
+        root.write("intel-rapl:0:2/energy_uj", "7000000");
+        root.write("intel-rapl:1/energy_uj", "16000000");
+        let zones = sampler.sample_at(&root).unwrap();
+
+        let watts = |name: &str| zones.iter().find(|z| z.name == name).unwrap().watts;
+        // 1 J to the wrap plus 9 J after it
+        assert!((watts("package-0") - 10.0).abs() < 0.5);
+        assert!((watts("package-0 dram") - 2.0).abs() < 0.1);
+        assert!(zones.iter().any(|z| z.package && z.name == "package-0"));
+        // the platform zone already has the package in it
+        assert!((watts("psys") - 15.0).abs() < 0.5);
+        let total: f64 = zones.iter().filter(|z| z.package).map(|z| z.watts).sum();
+        assert!((total - 10.0).abs() < 0.5);
+    }
+}
@@ -0,0 +1,346 @@
+/*
+- Temperature sensors from hwmon and the thermal zones. sys.cpu_temp() only
+- ever looks at thermal_zone0 or hwmon0 which is often the wrong thing
+*/
+use crate::collector::{Collector, Sample};
+use crate::sysfs::{self, read_number, read_text};
+use std::fs;
+use std::io;
+use std::path::Path;
+use systemstat::{Platform, System};
+
+const HWMON: &str = "/sys/class/hwmon";
+const THERMAL: &str = "/sys/class/thermal";
+
+// chips that are the cpu package, in order of preference
+const CPU_CHIPS: &[&str] = &[
+    "coretemp",
+    "k10temp",
+    "zenpower",
+    "cpu_thermal",
+    "x86_pkg_temp",
+];
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct TempSensor {
+    // stable key for the history, like "hwmon2/temp1" or "thermal_zone0"
+    pub id: String,
+    pub chip: String,
+    pub label: String,
+    pub celsius: f32,
+    pub max: Option<f32>,
+    pub crit: Option<f32>,
+}
+
+impl TempSensor {
+    // "coretemp Package id 0", used for the legend
+    pub fn name(&self) -> String {
+        format!("{} {}", self.chip, self.label)
+    }
+
+    // how close we are to the limits hwmon gave us
+    pub fn alarm(&self) -> Option<&'static str> {
+        let over = |limit: Option<f32>| limit.is_some_and(|l| self.celsius >= l);
+        if over(self.crit) {
+            Some("CRIT")
+        } else if over(self.max) {
+            Some("MAX")
+        } else {
+            None
+        }
+    }
+}
+
+// Non temperature hwmon channels
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub enum ReadingKind {
+    Fan,
+    Voltage,
+    Power,
+}
+
+impl ReadingKind {
+    // file prefix, the file holding the value, and what to multiply raw values by
+    fn files(self) -> (&'static str, &'static [&'static str], f64) {
+        match self {
+            ReadingKind::Fan => ("fan", &["input"], 1.0),
+            ReadingKind::Voltage => ("in", &["input"], 0.001),
+            // some chips only have the instant reading
+            ReadingKind::Power => ("power", &["average", "input"], 0.000_001),
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            ReadingKind::Fan => "RPM",
+            ReadingKind::Voltage => "V",
+            ReadingKind::Power => "W",
+        }
+    }
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(the_serde::Serialize, the_serde::Deserialize),
+    serde(crate = "the_serde")
+)]
+pub struct Reading {
+    // stable key like "hwmon3/fan1"
+    pub id: String,
+    pub chip: String,
+    pub label: String,
+    pub kind: ReadingKind,
+    pub value: f64,
+    // limits from hwmon where the chip has them
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub crit: Option<f64>,
+}
+
+// Fans, voltages and power draw from every hwmon chip
+pub fn read_readings() -> Vec<Reading> {
+    read_hwmon_readings(Path::new(HWMON))
+}
+
+// Every temperature we can find, hwmon first then thermal zones
+pub fn read_temps() -> Vec<TempSensor> {
+    let mut sensors = read_hwmon_temps(Path::new(HWMON));
+    sensors.extend(read_thermal_zones(Path::new(THERMAL)));
+    sensors
+}
+
+// every temperature plus the fan, voltage and power readings
+pub struct SensorCollector;
+
+impl Collector for SensorCollector {
+    fn name(&self) -> &'static str {
+        "sensors"
+    }
+
+    fn collect(&mut self, sys: &System) -> io::Result<Sample> {
+        let temps = read_temps();
+        // prefer a real cpu sensor over whatever zone systemstat finds
+        let cpu_temp = match cpu_sensor(&temps) {
+            Some(sensor) => Some(sensor.celsius),
+            None => sys.cpu_temp().ok(),
+        };
+        Ok(Sample::Sensors {
+            temps,
+            readings: read_readings(),
+            cpu_temp,
+        })
+    }
+}
+
+// The sensor that best stands for "the cpu", if there is one
+pub fn cpu_sensor(sensors: &[TempSensor]) -> Option<&TempSensor> {
+    CPU_CHIPS
+        .iter()
+        .find_map(|chip| sensors.iter().find(|s| s.chip == *chip))
+}
+
+// temperatures are in millidegrees
+fn read_milli(path: &Path) -> Option<f32> {
+    read_number(path).map(|v: f64| (v / 1000.0) as f32)
+}
+
+// Channel numbers for files like "temp3_input", sorted
+fn channels(dir: &Path, prefix: &str, suffix: &str) -> Vec<u32> {
+    let mut found: Vec<u32> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.strip_prefix(prefix)?
+                    .strip_suffix(suffix)?
+                    .strip_suffix('_')?
+                    .parse()
+                    .ok()
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    found.sort_unstable();
+    found
+}
+
+fn read_hwmon_temps(root: &Path) -> Vec<TempSensor> {
+    let mut sensors = Vec::new();
+    for (hwmon, dir) in sysfs::dirs(root) {
+        let chip = read_text(&dir.join("name")).unwrap_or_else(|| hwmon.clone());
+        for n in channels(&dir, "temp", "input") {
+            let file = |suffix: &str| dir.join(format!("temp{}_{}", n, suffix));
+            let celsius = match read_milli(&file("input")) {
+                Some(c) => c,
+                None => continue,
+            };
+            sensors.push(TempSensor {
+                id: format!("{}/temp{}", hwmon, n),
+                chip: chip.clone(),
+                label: read_text(&file("label")).unwrap_or_else(|| format!("temp{}", n)),
+                celsius,
+                max: read_milli(&file("max")),
+                crit: read_milli(&file("crit")),
+            });
+        }
+    }
+    sensors
+}
+
+fn read_hwmon_readings(root: &Path) -> Vec<Reading> {
+    let mut readings = Vec::new();
+    for (hwmon, dir) in sysfs::dirs(root) {
+        let chip = read_text(&dir.join("name")).unwrap_or_else(|| hwmon.clone());
+        for kind in [ReadingKind::Fan, ReadingKind::Voltage, ReadingKind::Power] {
+            let (prefix, inputs, scale) = kind.files();
+            for input in inputs {
+                for n in channels(&dir, prefix, input) {
+                    let id = format!("{}/{}{}", hwmon, prefix, n);
+                    // already have it from a preferred file
+                    if readings.iter().any(|r: &Reading| r.id == id) {
+                        continue;
+                    }
+                    let file = |suffix: &str| dir.join(format!("{}{}_{}", prefix, n, suffix));
+                    let scaled = |suffix: &str| read_number(&file(suffix)).map(|v: f64| v * scale);
+                    let Some(value) = scaled(input) else {
+                        continue;
+                    };
+                    readings.push(Reading {
+                        id,
+                        chip: chip.clone(),
+                        label: read_text(&file("label"))
+                            .unwrap_or_else(|| format!("{}{}", prefix, n)),
+                        kind,
+                        value,
+                        min: scaled("min"),
+                        max: scaled("max").or_else(|| scaled("cap")),
+                        crit: scaled("crit"),
+                    });
+                }
+            }
+        }
+    }
+    readings
+}
+
+fn read_thermal_zones(root: &Path) -> Vec<TempSensor> {
+    let mut sensors = Vec::new();
+    for (zone, dir) in sysfs::dirs(root) {
+        if !zone.starts_with("thermal_zone") {
+            continue;
+        }
+        let celsius = match read_milli(&dir.join("temp")) {
+            Some(c) => c,
+            None => continue,
+        };
+        // the critical trip point is where the kernel shuts things down
+        let crit = (0..16).find_map(|i| {
+            match read_text(&dir.join(format!("trip_point_{}_type", i)))?.as_str() {
+                "critical" => read_milli(&dir.join(format!("trip_point_{}_temp", i))),
+                _ => None,
+            }
+        });
+        let label = read_text(&dir.join("type")).unwrap_or_else(|| zone.clone());
+        sensors.push(TempSensor {
+            id: zone,
+            chip: if CPU_CHIPS.contains(&label.as_str()) {
+                label.clone()
+            } else {
+                "thermal".to_owned()
+            },
+            label,
+            celsius,
+            max: None,
+            crit,
+        });
+    }
+    sensors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cpu_sensor, read_hwmon_readings, read_hwmon_temps, read_thermal_zones};
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn hwmon_temps_with_labels_and_limits() {
+        let root = TestDir::with_files(
+            "hwmon",
+            &[
+                ("hwmon1/name", "coretemp\n"),
+                ("hwmon1/temp1_input", "45000\n"),
+                ("hwmon1/temp1_label", "Package id 0\n"),
+                ("hwmon1/temp1_max", "80000\n"),
+                ("hwmon1/temp1_crit", "100000\n"),
+                ("hwmon1/temp2_input", "41500\n"),
+                ("hwmon0/name", "acpitz\n"),
+                ("hwmon0/temp1_input", "27800\n"),
+            ],
+        );
+        let sensors = read_hwmon_temps(&root);
+
+        assert_eq!(sensors.len(), 3);
+        assert_eq!(sensors[0].name(), "acpitz temp1");
+        assert_eq!(sensors[1].name(), "coretemp Package id 0");
+        assert_eq!(sensors[1].celsius, 45.0);
+        assert_eq!(sensors[1].max, Some(80.0));
+        assert_eq!(sensors[1].crit, Some(100.0));
+        assert_eq!(sensors[2].label, "temp2");
+        assert_eq!(cpu_sensor(&sensors).unwrap().id, "hwmon1/temp1");
+    }
+
+    #[test]
+    fn hwmon_fans_volts_and_power() {
+        let root = TestDir::with_files(
+            "readings",
+            &[
+                ("hwmon0/name", "nct6775\n"),
+                ("hwmon0/fan2_input", "1200\n"),
+                ("hwmon0/fan2_min", "300\n"),
+                ("hwmon0/in0_input", "1136\n"),
+                ("hwmon0/in0_label", "Vcore\n"),
+                ("hwmon0/power1_average", "15500000\n"),
+                ("hwmon0/power1_input", "99000000\n"),
+            ],
+        );
+        let readings = read_hwmon_readings(&root);
+
+        assert_eq!(readings.len(), 3);
+        assert_eq!(readings[0].label, "fan2");
+        assert_eq!(readings[0].min, Some(300.0));
+        assert_eq!(readings[1].label, "Vcore");
+        assert!((readings[1].value - 1.136).abs() < 1e-9);
+        // average wins over the instant reading
+        assert_eq!(readings[2].value, 15.5);
+    }
+
+    #[test]
+    fn thermal_zone_critical_trip() {
+        let root = TestDir::with_files(
+            "thermal",
+            &[
+                ("thermal_zone0/type", "x86_pkg_temp\n"),
+                ("thermal_zone0/temp", "52000\n"),
+                ("thermal_zone0/trip_point_0_type", "passive\n"),
+                ("thermal_zone0/trip_point_0_temp", "90000\n"),
+                ("thermal_zone0/trip_point_1_type", "critical\n"),
+                ("thermal_zone0/trip_point_1_temp", "105000\n"),
+                ("cooling_device0/type", "Processor\n"),
+            ],
+        );
+        let sensors = read_thermal_zones(&root);
+
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors[0].chip, "x86_pkg_temp");
+        assert_eq!(sensors[0].crit, Some(105.0));
+    }
+}
@@ -0,0 +1,41 @@
+/*
+- Plain numbers for the JSON output. ByteSize would come out as "6.3 GB" and
+  Duration as {secs, nanos}, neither of which a script wants to parse
+*/
+use the_serde::{Deserialize, Deserializer, Serializer};
+
+// a ByteSize as a count of bytes
+pub mod bytes {
+    use super::*;
+    use bytesize::ByteSize;
+
+    pub fn serialize<S: Serializer>(size: &ByteSize, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(size.as_u64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ByteSize, D::Error> {
+        u64::deserialize(deserializer).map(ByteSize::b)
+    }
+}
+
+// an optional Duration as whole seconds
+pub mod opt_secs {
+    use super::*;
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(d) => serializer.serialize_some(&d.as_secs()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|secs| secs.map(Duration::from_secs))
+    }
+}
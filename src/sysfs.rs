@@ -0,0 +1,29 @@
+/*
+- Small readers for sysfs. Every attribute is one value per file with a
+  trailing newline, a missing or unreadable file is just None
+*/
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// a file holding a single number
+pub fn read_number<T: FromStr>(path: &Path) -> Option<T> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+pub fn read_text(path: &Path) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_owned())
+}
+
+// entries of a sysfs class dir sorted so the order doesnt change between ticks
+pub fn dirs(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut dirs: Vec<_> = match fs::read_dir(root) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| (e.file_name().to_string_lossy().into_owned(), e.path()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    dirs.sort();
+    dirs
+}
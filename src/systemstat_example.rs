@@ -155,7 +155,6 @@ fn get_stat() {
 mod tests {
     use crate::systemstat_example::get_stat;
 
-    use super::System;
     #[test]
     fn system_stat_test() {
         get_stat();
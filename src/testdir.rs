@@ -0,0 +1,48 @@
+/*
+- Throwaway directories for the tests that fake sysfs. They go away when
+  dropped so a failing assert doesnt leave one behind in the temp dir
+*/
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    // sys-mon-<name>-<pid> under the temp dir, empty to start with
+    pub fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("sys-mon-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        TestDir(root)
+    }
+
+    // a new dir with these files in it, paths are relative to the dir
+    pub fn with_files(name: &str, files: &[(&str, &str)]) -> Self {
+        let dir = TestDir::new(name);
+        for (path, contents) in files {
+            dir.write(path, contents);
+        }
+        dir
+    }
+
+    pub fn write(&self, path: &str, contents: &str) {
+        let path = self.0.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}